@@ -1,42 +1,107 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer as SolTransfer};
-use anchor_spl::token::{self, Token, TokenAccount, Transfer as TokenTransfer};
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use property_registry::program::PropertyRegistry;
+use property_registry::{Reputation, ReputationEvent, ReputationRole, ESCROW_AUTHORITY_SEED};
+use std::cmp::Ordering;
 
 declare_id!("7aa5fKvc4ejGWZdbxn8DmH5RVhofgwURXibcp3RtstAg");
 
+/// Rental Agreement program, the only program allowed to open an escrow
+pub mod rental_agreement_program {
+    use super::*;
+
+    declare_id!("FD4wJxjtivZBoujfYACCUNo1D7ygsbn9Gx26cU1UqPXV");
+}
+
+/// Primary tenant plus up to four co-tenants
+pub const MAX_CONTRIBUTORS: u8 = 5;
+
 #[program]
 pub mod escrow {
     use super::*;
 
-    pub fn deposit_to_escrow(
-        ctx: Context<DepositToEscrow>,
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn deposit_to_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositToEscrow<'info>>,
         amount: u64,
+        response_window: i64,
+        lease_end_date: i64,
+        inspection_window: i64,
+        yield_config: Option<YieldConfig>,
     ) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
 
         require!(amount > 0, EscrowError::InvalidAmount);
+        require!(response_window > 0, EscrowError::InvalidWindow);
+        require!(inspection_window > 0, EscrowError::InvalidWindow);
         require!(!escrow.is_initialized, EscrowError::EscrowAlreadyInitialized);
 
-        // Transfer USDC from tenant to escrow token account
+        if let Some(config) = yield_config {
+            require!(config.tenant_share_bps <= 10_000, EscrowError::InvalidYieldShare);
+            // Strategies move the deposit with plain transfers, so hooked mints can't use them
+            require!(
+                !mint_has_extension(&ctx.accounts.mint.to_account_info(), ExtensionType::TransferHook)?,
+                EscrowError::YieldUnsupportedMint
+            );
+        }
+
+        let parties = AgreementParties::try_from_account(&ctx.accounts.rental_agreement)?;
+        require_keys_eq!(parties.tenant, ctx.accounts.tenant.key(), EscrowError::PartyMismatch);
+        require_keys_eq!(parties.landlord, ctx.accounts.landlord.key(), EscrowError::PartyMismatch);
+
+        // Transfer the deposit from tenant to escrow token account
         let transfer_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            TokenTransfer {
+            TransferChecked {
                 from: ctx.accounts.tenant_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.escrow_token_account.to_account_info(),
                 authority: ctx.accounts.tenant.to_account_info(),
             },
-        );
-        token::transfer(transfer_ctx, amount)?;
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        // Hold what actually arrived, after any transfer fee
+        ctx.accounts.escrow_token_account.reload()?;
+        let received = ctx.accounts.escrow_token_account.amount;
+        require!(received > 0, EscrowError::InvalidAmount);
 
         escrow.rental_agreement = ctx.accounts.rental_agreement.key();
         escrow.tenant = ctx.accounts.tenant.key();
         escrow.landlord = ctx.accounts.landlord.key();
         escrow.escrow_token_account = ctx.accounts.escrow_token_account.key();
-        escrow.amount = amount;
+        escrow.mint = ctx.accounts.mint.key();
+        escrow.amount = received;
         escrow.is_released = false;
+        escrow.is_disputed = false;
         escrow.is_initialized = true;
         escrow.bump = ctx.bumps.escrow;
         escrow.created_at = Clock::get()?.unix_timestamp;
+        escrow.response_window = response_window;
+        escrow.proposed_deduction = 0;
+        escrow.deduction_reason_hash = [0; 32];
+        escrow.deduction_deadline = 0;
+        escrow.lease_end_date = lease_end_date;
+        escrow.inspection_window = inspection_window;
+        escrow.contributions = vec![DepositContribution {
+            contributor: escrow.tenant,
+            amount: received,
+        }];
+        escrow.yield_config = yield_config;
+        escrow.invested_amount = 0;
 
         emit!(EscrowCreated {
             escrow: escrow.key(),
@@ -49,11 +114,236 @@ pub mod escrow {
         Ok(())
     }
 
-    pub fn release_to_tenant(ctx: Context<ReleaseToTenant>) -> Result<()> {
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn add_contribution<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddContribution<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(amount > 0, EscrowError::InvalidAmount);
+        require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+        require!(escrow.invested_amount == 0, EscrowError::DepositInvested);
+        require!(
+            escrow.contributions.len() < MAX_CONTRIBUTORS as usize,
+            EscrowError::TooManyContributors
+        );
+
+        // Co-tenant pays their share of the deposit straight into the vault
+        let vault_before = ctx.accounts.escrow_token_account.amount;
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.contributor_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.escrow_token_account.to_account_info(),
+                authority: ctx.accounts.contributor.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        ctx.accounts.escrow_token_account.reload()?;
+        let received = ctx.accounts.escrow_token_account.amount - vault_before;
+        require!(received > 0, EscrowError::InvalidAmount);
+
+        escrow.amount += received;
+        escrow.contributions.push(DepositContribution {
+            contributor: ctx.accounts.contributor.key(),
+            amount: received,
+        });
+
+        emit!(ContributionAdded {
+            escrow: escrow.key(),
+            contributor: ctx.accounts.contributor.key(),
+            amount: received,
+            total: escrow.amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn release_to_tenant<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReleaseToTenant<'info>>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.is_initialized, EscrowError::EscrowNotInitialized);
+        require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::EscrowDisputed);
+        require!(escrow.invested_amount == 0, EscrowError::DepositInvested);
+
+        let (co_tenant_accounts, hook_accounts) =
+            escrow.split_remaining_accounts(ctx.remaining_accounts);
+        refund_tenants(
+            escrow,
+            &ctx.accounts.mint,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.tenant_token_account,
+            co_tenant_accounts,
+            hook_accounts,
+            &ctx.accounts.token_program,
+            escrow.amount,
+        )?;
+
+        escrow.is_released = true;
+
+        emit!(EscrowReleased {
+            escrow: escrow.key(),
+            recipient: escrow.tenant,
+            amount: escrow.amount,
+            released_to: ReleaseType::Tenant,
+        });
+
+        Ok(())
+    }
+
+    pub fn propose_deduction(
+        ctx: Context<ProposeDeduction>,
+        landlord_amount: u64,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.is_initialized, EscrowError::EscrowNotInitialized);
+        require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::EscrowDisputed);
+        require!(!escrow.has_pending_deduction(), EscrowError::DeductionPending);
+        require!(landlord_amount > 0, EscrowError::InvalidAmount);
+        require!(landlord_amount <= escrow.amount, EscrowError::InsufficientFunds);
+
+        // Deductions are claimed during the inspection window, never mid-tenancy
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= escrow.lease_end_date, EscrowError::LeaseNotEnded);
+        require!(
+            now < escrow.refund_available_at(),
+            EscrowError::InspectionWindowClosed
+        );
+
+        escrow.proposed_deduction = landlord_amount;
+        escrow.deduction_reason_hash = reason_hash;
+        escrow.deduction_deadline = now + escrow.response_window;
+
+        emit!(DeductionProposed {
+            escrow: escrow.key(),
+            landlord: escrow.landlord,
+            landlord_amount,
+            reason_hash,
+            deadline: escrow.deduction_deadline,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_deduction<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptDeduction<'info>>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+        require!(escrow.has_pending_deduction(), EscrowError::NoPendingDeduction);
+
+        let (co_tenant_accounts, hook_accounts) =
+            escrow.split_remaining_accounts(ctx.remaining_accounts);
+        settle_deduction(
+            escrow,
+            &ctx.accounts.mint,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.landlord_token_account,
+            &ctx.accounts.tenant_token_account,
+            co_tenant_accounts,
+            hook_accounts,
+            &ctx.accounts.token_program,
+        )
+    }
+
+    pub fn finalize_deduction<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinalizeDeduction<'info>>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+        require!(escrow.has_pending_deduction(), EscrowError::NoPendingDeduction);
+        require!(
+            Clock::get()?.unix_timestamp >= escrow.deduction_deadline,
+            EscrowError::DeadlineNotReached
+        );
+
+        // The tenant did not respond in time, so the proposal stands
+        let (co_tenant_accounts, hook_accounts) =
+            escrow.split_remaining_accounts(ctx.remaining_accounts);
+        settle_deduction(
+            escrow,
+            &ctx.accounts.mint,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.landlord_token_account,
+            &ctx.accounts.tenant_token_account,
+            co_tenant_accounts,
+            hook_accounts,
+            &ctx.accounts.token_program,
+        )
+    }
+
+    pub fn claim_refund<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRefund<'info>>) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
 
         require!(escrow.is_initialized, EscrowError::EscrowNotInitialized);
         require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::EscrowDisputed);
+        require!(!escrow.has_pending_deduction(), EscrowError::DeductionPending);
+        require!(escrow.invested_amount == 0, EscrowError::DepositInvested);
+        require!(
+            Clock::get()?.unix_timestamp >= escrow.refund_available_at(),
+            EscrowError::InspectionWindowOpen
+        );
+
+        // No deduction was claimed in time, so the full deposit goes back to the tenants
+        let (co_tenant_accounts, hook_accounts) =
+            escrow.split_remaining_accounts(ctx.remaining_accounts);
+        refund_tenants(
+            escrow,
+            &ctx.accounts.mint,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.tenant_token_account,
+            co_tenant_accounts,
+            hook_accounts,
+            &ctx.accounts.token_program,
+            escrow.amount,
+        )?;
+
+        escrow.is_released = true;
+
+        emit!(EscrowReleased {
+            escrow: escrow.key(),
+            recipient: escrow.tenant,
+            amount: escrow.amount,
+            released_to: ReleaseType::Tenant,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_lease_end(ctx: Context<UpdateLeaseEnd>, lease_end_date: i64) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+
+        escrow.lease_end_date = lease_end_date;
+
+        emit!(LeaseEndUpdated {
+            escrow: escrow.key(),
+            lease_end_date,
+            refund_available_at: escrow.refund_available_at(),
+        });
+
+        Ok(())
+    }
+
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn close_escrow<'info>(ctx: Context<'_, '_, '_, 'info, CloseEscrow<'info>>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+
+        require!(escrow.is_released, EscrowError::EscrowNotReleased);
 
         let seeds = &[
             b"escrow",
@@ -62,36 +352,93 @@ pub mod escrow {
         ];
         let signer = &[&seeds[..]];
 
-        // Transfer SOL from escrow PDA back to tenant
-        let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            SolTransfer {
-                from: escrow.to_account_info(),
-                to: ctx.accounts.tenant.to_account_info(),
+        // Sweep anything left in the vault back to tenant before closing it
+        let remaining = ctx.accounts.escrow_token_account.amount;
+        if remaining > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.tenant_token_account.to_account_info(),
+                    authority: escrow.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_ctx, remaining, ctx.accounts.mint.decimals)?;
+        }
+
+        harvest_withheld_fees(
+            &ctx.accounts.mint,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
+        // Tenant paid for the vault, so its rent goes back to them
+        let close_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.tenant.to_account_info(),
+                authority: escrow.to_account_info(),
             },
             signer,
         );
-        transfer(transfer_ctx, escrow.amount)?;
-
-        escrow.is_released = true;
+        token_interface::close_account(close_ctx)?;
 
-        emit!(EscrowReleased {
+        emit!(EscrowClosed {
             escrow: escrow.key(),
-            recipient: escrow.tenant,
+            rental_agreement: escrow.rental_agreement,
+            tenant: escrow.tenant,
+            landlord: escrow.landlord,
+            mint: escrow.mint,
             amount: escrow.amount,
-            released_to: ReleaseType::Tenant,
+            swept_amount: remaining,
+            created_at: escrow.created_at,
         });
 
         Ok(())
     }
 
-    pub fn release_to_landlord(ctx: Context<ReleaseToLandlord>, amount: u64) -> Result<()> {
+    pub fn freeze_escrow(ctx: Context<FreezeEscrow>) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
 
         require!(escrow.is_initialized, EscrowError::EscrowNotInitialized);
         require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
-        require!(amount <= escrow.amount, EscrowError::InsufficientFunds);
+        require!(!escrow.is_disputed, EscrowError::EscrowDisputed);
+
+        escrow.is_disputed = true;
+        // A dispute supersedes any pending deduction proposal
+        escrow.proposed_deduction = 0;
+        escrow.deduction_deadline = 0;
+
+        emit!(EscrowFrozen {
+            escrow: escrow.key(),
+            rental_agreement: escrow.rental_agreement,
+        });
+
+        Ok(())
+    }
+
+    /// Tenant or landlord moves the deposit into the yield strategy chosen at deposit time.
+    /// Strategy-specific accounts follow as remaining accounts.
+    pub fn invest_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, InvestDeposit<'info>>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.yield_config.is_some(), EscrowError::YieldNotEnabled);
+        require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
+        require!(!escrow.is_disputed, EscrowError::EscrowDisputed);
+        require!(!escrow.has_pending_deduction(), EscrowError::DeductionPending);
+        require!(escrow.invested_amount == 0, EscrowError::DepositInvested);
+        require!(
+            Clock::get()?.unix_timestamp < escrow.lease_end_date,
+            EscrowError::LeaseEnded
+        );
 
+        let amount = escrow.amount;
         let seeds = &[
             b"escrow",
             escrow.rental_agreement.as_ref(),
@@ -99,40 +446,118 @@ pub mod escrow {
         ];
         let signer = &[&seeds[..]];
 
-        // Transfer SOL from escrow PDA to landlord
-        let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            SolTransfer {
-                from: escrow.to_account_info(),
-                to: ctx.accounts.landlord.to_account_info(),
+        let strategy_ctx = CpiContext::new_with_signer(
+            ctx.accounts.strategy_program.to_account_info(),
+            StrategyAccounts {
+                depositor: escrow.to_account_info(),
+                position: ctx.accounts.strategy_position.to_account_info(),
+                depositor_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                strategy_vault: ctx.accounts.strategy_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
             },
             signer,
-        );
-        transfer(transfer_ctx, amount)?;
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        yield_adapter::deposit(strategy_ctx, amount)?;
 
-        escrow.is_released = true;
+        escrow.invested_amount = amount;
 
-        emit!(EscrowReleased {
+        emit!(DepositInvested {
             escrow: escrow.key(),
-            recipient: escrow.landlord,
+            strategy: ctx.accounts.strategy_program.key(),
             amount,
-            released_to: ReleaseType::Landlord,
         });
 
         Ok(())
     }
 
-    pub fn partial_deduct(
-        ctx: Context<PartialDeduct>,
+    /// Tenant or landlord pulls the deposit back out of the strategy once it is needed for
+    /// settlement, paying the landlord their share of the yield. Strategy-specific accounts
+    /// follow as remaining accounts.
+    pub fn divest_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, DivestDeposit<'info>>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+
+        require!(escrow.invested_amount > 0, EscrowError::DepositNotInvested);
+        require!(
+            Clock::get()?.unix_timestamp >= escrow.lease_end_date
+                || escrow.is_disputed
+                || escrow.has_pending_deduction(),
+            EscrowError::SettlementNotDue
+        );
+
+        let seeds = &[
+            b"escrow",
+            escrow.rental_agreement.as_ref(),
+            &[escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let vault_before = ctx.accounts.escrow_token_account.amount;
+        let strategy_ctx = CpiContext::new_with_signer(
+            ctx.accounts.strategy_program.to_account_info(),
+            StrategyAccounts {
+                depositor: escrow.to_account_info(),
+                position: ctx.accounts.strategy_position.to_account_info(),
+                depositor_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                strategy_vault: ctx.accounts.strategy_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        yield_adapter::withdraw(strategy_ctx)?;
+
+        ctx.accounts.escrow_token_account.reload()?;
+        let returned = ctx.accounts.escrow_token_account.amount - vault_before;
+
+        // Any loss is borne by the deposit; any gain is split by the agreed ratio
+        let earned = returned.saturating_sub(escrow.invested_amount);
+        let tenant_share_bps = escrow.yield_config.map_or(0, |c| c.tenant_share_bps);
+        let tenant_yield = (earned as u128 * tenant_share_bps as u128 / 10_000) as u64;
+        let landlord_yield = earned - tenant_yield;
+
+        if landlord_yield > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.landlord_token_account.to_account_info(),
+                    authority: escrow.to_account_info(),
+                },
+                signer,
+            );
+            token_interface::transfer_checked(transfer_ctx, landlord_yield, ctx.accounts.mint.decimals)?;
+        }
+
+        escrow.amount = returned - landlord_yield;
+        escrow.invested_amount = 0;
+
+        emit!(DepositDivested {
+            escrow: escrow.key(),
+            returned,
+            tenant_yield,
+            landlord_yield,
+        });
+
+        Ok(())
+    }
+
+    pub fn settle_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleDispute<'info>>,
         landlord_amount: u64,
-        reason: String,
     ) -> Result<()> {
         let escrow = &mut ctx.accounts.escrow;
 
         require!(escrow.is_initialized, EscrowError::EscrowNotInitialized);
         require!(!escrow.is_released, EscrowError::EscrowAlreadyReleased);
-        require!(landlord_amount < escrow.amount, EscrowError::InvalidAmount);
-        require!(reason.len() <= 500, EscrowError::ReasonTooLong);
+        require!(escrow.is_disputed, EscrowError::EscrowNotDisputed);
+        require!(escrow.invested_amount == 0, EscrowError::DepositInvested);
+        require!(landlord_amount <= escrow.amount, EscrowError::InsufficientFunds);
 
         let tenant_amount = escrow.amount - landlord_amount;
 
@@ -143,43 +568,373 @@ pub mod escrow {
         ];
         let signer = &[&seeds[..]];
 
-        // Transfer SOL to landlord
-        let transfer_to_landlord = CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            SolTransfer {
-                from: escrow.to_account_info(),
-                to: ctx.accounts.landlord.to_account_info(),
-            },
-            signer,
-        );
-        transfer(transfer_to_landlord, landlord_amount)?;
-
-        // Transfer remaining SOL to tenant
-        let transfer_to_tenant = CpiContext::new_with_signer(
-            ctx.accounts.system_program.to_account_info(),
-            SolTransfer {
-                from: escrow.to_account_info(),
-                to: ctx.accounts.tenant.to_account_info(),
-            },
-            signer,
-        );
-        transfer(transfer_to_tenant, tenant_amount)?;
+        let (co_tenant_accounts, hook_accounts) =
+            escrow.split_remaining_accounts(ctx.remaining_accounts);
+
+        // Transfer the arbitrated share to landlord
+        if landlord_amount > 0 {
+            let transfer_to_landlord = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.landlord_token_account.to_account_info(),
+                    authority: escrow.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(hook_accounts.to_vec());
+            transfer_checked_with_hook(transfer_to_landlord, landlord_amount, ctx.accounts.mint.decimals)?;
+        }
+
+        // Transfer the rest to the tenants
+        refund_tenants(
+            escrow,
+            &ctx.accounts.mint,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.tenant_token_account,
+            co_tenant_accounts,
+            hook_accounts,
+            &ctx.accounts.token_program,
+            tenant_amount,
+        )?;
 
         escrow.is_released = true;
+        escrow.is_disputed = false;
+
+        // Whoever the arbitrator awarded most of the deposit won the dispute
+        let landlord_share = landlord_amount as u128 * 2;
+        let (landlord_event, tenant_event) = match landlord_share.cmp(&(escrow.amount as u128)) {
+            Ordering::Greater => (ReputationEvent::DisputeWon, ReputationEvent::DisputeLost),
+            Ordering::Less => (ReputationEvent::DisputeLost, ReputationEvent::DisputeWon),
+            Ordering::Equal => (ReputationEvent::DisputeSplit, ReputationEvent::DisputeSplit),
+        };
+        for (reputation, event) in [
+            (ctx.accounts.landlord_reputation.to_account_info(), landlord_event),
+            (ctx.accounts.tenant_reputation.to_account_info(), tenant_event),
+        ] {
+            record_reputation(
+                reputation,
+                ctx.accounts.escrow_authority.to_account_info(),
+                ctx.bumps.escrow_authority,
+                ctx.accounts.property_program.to_account_info(),
+                event,
+            )?;
+        }
 
-        emit!(PartialDeduction {
+        emit!(DisputeSettled {
             escrow: escrow.key(),
             landlord: escrow.landlord,
             tenant: escrow.tenant,
             landlord_amount,
             tenant_amount,
-            reason,
         });
 
         Ok(())
     }
 }
 
+/// Pays a pending deduction to the landlord and the rest of the deposit to the tenant.
+fn settle_deduction<'info>(
+    escrow: &mut Account<'info, Escrow>,
+    mint: &InterfaceAccount<'info, Mint>,
+    escrow_token_account: &InterfaceAccount<'info, TokenAccount>,
+    landlord_token_account: &InterfaceAccount<'info, TokenAccount>,
+    tenant_token_account: &InterfaceAccount<'info, TokenAccount>,
+    co_tenant_token_accounts: &[AccountInfo<'info>],
+    hook_accounts: &[AccountInfo<'info>],
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    require!(escrow.invested_amount == 0, EscrowError::DepositInvested);
+
+    let landlord_amount = escrow.proposed_deduction;
+    let tenant_amount = escrow.amount - landlord_amount;
+
+    let seeds = &[
+        b"escrow",
+        escrow.rental_agreement.as_ref(),
+        &[escrow.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Transfer the deduction to landlord
+    let transfer_to_landlord = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: escrow_token_account.to_account_info(),
+            mint: mint.to_account_info(),
+            to: landlord_token_account.to_account_info(),
+            authority: escrow.to_account_info(),
+        },
+        signer,
+    )
+    .with_remaining_accounts(hook_accounts.to_vec());
+    transfer_checked_with_hook(transfer_to_landlord, landlord_amount, mint.decimals)?;
+
+    // Transfer the rest of the deposit to the tenants
+    refund_tenants(
+        escrow,
+        mint,
+        escrow_token_account,
+        tenant_token_account,
+        co_tenant_token_accounts,
+        hook_accounts,
+        token_program,
+        tenant_amount,
+    )?;
+
+    escrow.is_released = true;
+    escrow.deduction_deadline = 0;
+
+    emit!(PartialDeduction {
+        escrow: escrow.key(),
+        landlord: escrow.landlord,
+        tenant: escrow.tenant,
+        landlord_amount,
+        tenant_amount,
+        reason_hash: escrow.deduction_reason_hash,
+    });
+
+    Ok(())
+}
+
+/// Pays `tenant_amount` back to the tenants pro rata to their deposit contributions.
+/// Co-tenant token accounts are passed as remaining accounts, in contribution order.
+fn refund_tenants<'info>(
+    escrow: &Account<'info, Escrow>,
+    mint: &InterfaceAccount<'info, Mint>,
+    escrow_token_account: &InterfaceAccount<'info, TokenAccount>,
+    tenant_token_account: &InterfaceAccount<'info, TokenAccount>,
+    co_tenant_token_accounts: &[AccountInfo<'info>],
+    hook_accounts: &[AccountInfo<'info>],
+    token_program: &Interface<'info, TokenInterface>,
+    tenant_amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        b"escrow",
+        escrow.rental_agreement.as_ref(),
+        &[escrow.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let shares = escrow.refund_shares(tenant_amount);
+    for (i, (contribution, share)) in escrow.contributions.iter().zip(shares).enumerate() {
+        if share == 0 {
+            continue;
+        }
+
+        let recipient = if i == 0 {
+            tenant_token_account.to_account_info()
+        } else {
+            let info = co_tenant_token_accounts
+                .get(i - 1)
+                .ok_or(EscrowError::MissingCoTenantAccount)?;
+            require_keys_eq!(*info.owner, token_program.key(), EscrowError::InvalidCoTenantAccount);
+            let token_account = TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(
+                token_account.owner,
+                contribution.contributor,
+                EscrowError::InvalidCoTenantAccount
+            );
+            require_keys_eq!(token_account.mint, escrow.mint, EscrowError::InvalidMint);
+            info.clone()
+        };
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: escrow_token_account.to_account_info(),
+                mint: mint.to_account_info(),
+                to: recipient,
+                authority: escrow.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(hook_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, share, mint.decimals)?;
+
+        emit!(DepositRefunded {
+            escrow: escrow.key(),
+            recipient: contribution.contributor,
+            amount: share,
+        });
+    }
+
+    Ok(())
+}
+
+/// Updates a wallet's reputation in the registry, signed by this program's escrow authority.
+fn record_reputation<'info>(
+    reputation: AccountInfo<'info>,
+    escrow_authority: AccountInfo<'info>,
+    authority_bump: u8,
+    property_program: AccountInfo<'info>,
+    event: ReputationEvent,
+) -> Result<()> {
+    let authority_seeds = &[ESCROW_AUTHORITY_SEED, &[authority_bump]];
+    let signer = &[&authority_seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        property_program,
+        property_registry::cpi::accounts::RecordReputation {
+            reputation,
+            authority: escrow_authority,
+        },
+        signer,
+    );
+    property_registry::cpi::record_reputation(cpi_ctx, event)
+}
+
+/// `token_interface::transfer_checked`, but forwarding the CPI context's remaining accounts
+/// so Token-2022 can invoke the mint's transfer hook with the extra accounts it needs.
+/// Every instruction that moves tokens in these programs takes those extra accounts, as
+/// resolved off chain from the mint's extra-account-meta list, as its trailing remaining
+/// accounts. Mints without a hook need none.
+pub fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let mut ix = spl_token_2022::instruction::transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    let mut account_infos = vec![
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+    ];
+    for info in ctx.remaining_accounts {
+        ix.accounts.push(AccountMeta {
+            pubkey: *info.key,
+            is_signer: info.is_signer,
+            is_writable: info.is_writable,
+        });
+        account_infos.push(info);
+    }
+    invoke_signed(&ix, &account_infos, ctx.signer_seeds).map_err(Into::into)
+}
+
+/// Moves transfer fees withheld in `token_account` to the mint, if the mint charges any.
+/// Token-2022 will not close an account still holding withheld fees, so every vault is
+/// harvested right before it closes. The mint receives the fees, so it must be writable.
+pub fn harvest_withheld_fees<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if !mint_has_extension(&mint_info, ExtensionType::TransferFeeConfig)? {
+        return Ok(());
+    }
+
+    let ix = spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+        token_program.key,
+        mint.to_account_info().key,
+        &[token_account.to_account_info().key],
+    )?;
+    invoke_signed(&ix, &[mint_info, token_account.to_account_info()], &[]).map_err(Into::into)
+}
+
+/// Whether a Token-2022 mint carries `extension`. Legacy SPL mints carry none.
+fn mint_has_extension(mint: &AccountInfo, extension: ExtensionType) -> Result<bool> {
+    let data = mint.try_borrow_data()?;
+    Ok(StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)
+        .and_then(|state| state.get_extension_types())
+        .map(|types| types.contains(&extension))
+        .unwrap_or(false))
+}
+
+/// Interface a yield strategy program implements so escrow can park deposits in it.
+/// A strategy exposes Anchor-style `deposit(amount)` and `withdraw()` instructions that
+/// take `StrategyAccounts` in order, followed by any accounts of its own. `withdraw`
+/// returns the depositor's whole position, yield included. Positions are opened ahead
+/// of time, since the escrow PDA cannot pay for them.
+pub mod yield_adapter {
+    use super::*;
+
+    pub fn deposit<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, StrategyAccounts<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        invoke_strategy(ctx, "deposit", &amount.to_le_bytes())
+    }
+
+    pub fn withdraw<'info>(ctx: CpiContext<'_, '_, '_, 'info, StrategyAccounts<'info>>) -> Result<()> {
+        invoke_strategy(ctx, "withdraw", &[])
+    }
+
+    fn invoke_strategy<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, StrategyAccounts<'info>>,
+        name: &str,
+        args: &[u8],
+    ) -> Result<()> {
+        let mut data = hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec();
+        data.extend_from_slice(args);
+
+        let mut accounts = ctx.accounts.to_account_metas(None);
+        let mut account_infos = ctx.accounts.to_account_infos();
+        for info in ctx.remaining_accounts {
+            accounts.push(AccountMeta {
+                pubkey: *info.key,
+                is_signer: info.is_signer,
+                is_writable: info.is_writable,
+            });
+            account_infos.push(info);
+        }
+
+        let ix = Instruction {
+            program_id: ctx.program.key(),
+            accounts,
+            data,
+        };
+        invoke_signed(&ix, &account_infos, ctx.signer_seeds).map_err(Into::into)
+    }
+}
+
+/// Leading accounts of every yield strategy instruction
+pub struct StrategyAccounts<'info> {
+    pub depositor: AccountInfo<'info>,
+    pub position: AccountInfo<'info>,
+    pub depositor_token_account: AccountInfo<'info>,
+    pub strategy_vault: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+impl ToAccountMetas for StrategyAccounts<'_> {
+    fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(*self.depositor.key, true),
+            AccountMeta::new(*self.position.key, false),
+            AccountMeta::new(*self.depositor_token_account.key, false),
+            AccountMeta::new(*self.strategy_vault.key, false),
+            AccountMeta::new_readonly(*self.mint.key, false),
+            AccountMeta::new_readonly(*self.token_program.key, false),
+        ]
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for StrategyAccounts<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.depositor.clone(),
+            self.position.clone(),
+            self.depositor_token_account.clone(),
+            self.strategy_vault.clone(),
+            self.mint.clone(),
+            self.token_program.clone(),
+        ]
+    }
+}
+
 #[derive(Accounts)]
 pub struct DepositToEscrow<'info> {
     #[account(
@@ -191,8 +946,11 @@ pub struct DepositToEscrow<'info> {
     )]
     pub escrow: Account<'info, Escrow>,
 
-    /// CHECK: Rental agreement from rental-agreement program
-    pub rental_agreement: UncheckedAccount<'info>,
+    /// Signed by the rental-agreement program, which funds the escrow while creating the lease
+    #[account(
+        owner = rental_agreement_program::ID @ EscrowError::InvalidRentalAgreement
+    )]
+    pub rental_agreement: Signer<'info>,
 
     #[account(mut)]
     pub tenant: Signer<'info>,
@@ -200,71 +958,466 @@ pub struct DepositToEscrow<'info> {
     /// CHECK: Landlord public key
     pub landlord: UncheckedAccount<'info>,
 
-    #[account(mut)]
-    pub tenant_token_account: Account<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == mint.key() @ EscrowError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    /// Vault owned by the escrow PDA, created here so the tenant never controls it
+    #[account(
+        init,
+        payer = tenant,
+        associated_token::mint = mint,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ReleaseToTenant<'info> {
+pub struct AddContribution<'info> {
     #[account(
         mut,
-        has_one = landlord,
-        has_one = tenant,
+        has_one = rental_agreement,
         seeds = [b"escrow", escrow.rental_agreement.as_ref()],
         bump = escrow.bump
     )]
     pub escrow: Account<'info, Escrow>,
 
-    pub landlord: Signer<'info>,
-
-    /// CHECK: Tenant public key
-    #[account(mut)]
-    pub tenant: UncheckedAccount<'info>,
+    /// Only the rental-agreement program can sign for the agreement PDA
+    pub rental_agreement: Signer<'info>,
 
-    pub system_program: Program<'info, System>,
-}
+    pub contributor: Signer<'info>,
 
-#[derive(Accounts)]
-pub struct ReleaseToLandlord<'info> {
+    #[account(
+        mut,
+        token::authority = contributor,
+        constraint = contributor_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub contributor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Co-tenant token accounts, then any transfer hook accounts, follow as remaining accounts
+#[derive(Accounts)]
+pub struct ReleaseToTenant<'info> {
     #[account(
         mut,
         has_one = landlord,
+        has_one = tenant,
         seeds = [b"escrow", escrow.rental_agreement.as_ref()],
         bump = escrow.bump
     )]
     pub escrow: Account<'info, Escrow>,
 
-    #[account(mut)]
     pub landlord: Signer<'info>,
 
-    pub system_program: Program<'info, System>,
+    /// CHECK: Tenant public key
+    pub tenant: UncheckedAccount<'info>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct PartialDeduct<'info> {
+pub struct ProposeDeduction<'info> {
     #[account(
         mut,
         has_one = landlord,
-        has_one = tenant,
         seeds = [b"escrow", escrow.rental_agreement.as_ref()],
         bump = escrow.bump
     )]
     pub escrow: Account<'info, Escrow>,
 
-    #[account(mut)]
     pub landlord: Signer<'info>,
+}
+
+/// Co-tenant token accounts, then any transfer hook accounts, follow as remaining accounts
+#[derive(Accounts)]
+pub struct AcceptDeduction<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        has_one = tenant,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    pub tenant: Signer<'info>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Co-tenant token accounts, then any transfer hook accounts, follow as remaining accounts
+#[derive(Accounts)]
+pub struct FinalizeDeduction<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        has_one = tenant,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
 
     /// CHECK: Tenant public key
+    pub tenant: UncheckedAccount<'info>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Co-tenant token accounts, then any transfer hook accounts, follow as remaining accounts
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        has_one = tenant,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: Tenant public key
+    pub tenant: UncheckedAccount<'info>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLeaseEnd<'info> {
+    #[account(
+        mut,
+        has_one = rental_agreement,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Only the rental-agreement program can sign for the agreement PDA
+    pub rental_agreement: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseEscrow<'info> {
+    #[account(
+        mut,
+        close = tenant,
+        has_one = rental_agreement,
+        has_one = tenant,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Only the rental-agreement program can sign for the agreement PDA
+    pub rental_agreement: Signer<'info>,
+
+    /// CHECK: Tenant public key, receives the rent of the closed accounts
     #[account(mut)]
     pub tenant: UncheckedAccount<'info>,
 
-    pub system_program: Program<'info, System>,
+    /// Writable for `harvest_withheld_fees`
+    #[account(mut, address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct FreezeEscrow<'info> {
+    #[account(
+        mut,
+        has_one = rental_agreement,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Only the rental-agreement program can sign for the agreement PDA
+    pub rental_agreement: Signer<'info>,
+}
+
+/// Co-tenant token accounts, then any transfer hook accounts, follow as remaining accounts
+#[derive(Accounts)]
+pub struct SettleDispute<'info> {
+    #[account(
+        mut,
+        has_one = rental_agreement,
+        has_one = landlord,
+        has_one = tenant,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// Only the rental-agreement program can sign for the agreement PDA
+    pub rental_agreement: Signer<'info>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    /// CHECK: Tenant public key
+    pub tenant: UncheckedAccount<'info>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = landlord_reputation.wallet == escrow.landlord
+            && landlord_reputation.role == ReputationRole::Landlord @ EscrowError::InvalidReputation
+    )]
+    pub landlord_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        constraint = tenant_reputation.wallet == escrow.tenant
+            && tenant_reputation.role == ReputationRole::Tenant @ EscrowError::InvalidReputation
+    )]
+    pub tenant_reputation: Box<Account<'info, Reputation>>,
+
+    /// CHECK: PDA that signs reputation updates in the registry
+    #[account(
+        seeds = [ESCROW_AUTHORITY_SEED],
+        bump
+    )]
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Strategy-specific accounts follow as remaining accounts
+#[derive(Accounts)]
+pub struct InvestDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        constraint = escrow.is_party(&authority.key()) @ EscrowError::NotEscrowParty
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The strategy program chosen at deposit time
+    #[account(
+        executable,
+        constraint = escrow.yield_config.map(|c| c.strategy) == Some(strategy_program.key())
+            @ EscrowError::InvalidStrategy
+    )]
+    pub strategy_program: UncheckedAccount<'info>,
+
+    /// CHECK: The escrow's position, validated by the strategy
+    #[account(mut)]
+    pub strategy_position: UncheckedAccount<'info>,
+
+    /// CHECK: The strategy's token vault, validated by the strategy
+    #[account(mut)]
+    pub strategy_vault: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Strategy-specific accounts follow as remaining accounts
+#[derive(Accounts)]
+pub struct DivestDeposit<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"escrow", escrow.rental_agreement.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        constraint = escrow.is_party(&authority.key()) @ EscrowError::NotEscrowParty
+    )]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    #[account(address = escrow.mint @ EscrowError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = escrow.escrow_token_account @ EscrowError::InvalidVault
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == escrow.mint @ EscrowError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The strategy program chosen at deposit time
+    #[account(
+        executable,
+        constraint = escrow.yield_config.map(|c| c.strategy) == Some(strategy_program.key())
+            @ EscrowError::InvalidStrategy
+    )]
+    pub strategy_program: UncheckedAccount<'info>,
+
+    /// CHECK: The escrow's position, validated by the strategy
+    #[account(mut)]
+    pub strategy_position: UncheckedAccount<'info>,
+
+    /// CHECK: The strategy's token vault, validated by the strategy
+    #[account(mut)]
+    pub strategy_vault: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[account]
@@ -274,11 +1427,107 @@ pub struct Escrow {
     pub tenant: Pubkey,             // 32
     pub landlord: Pubkey,           // 32
     pub escrow_token_account: Pubkey, // 32
+    pub mint: Pubkey,               // 32 - Deposit token mint
     pub amount: u64,                // 8
     pub is_released: bool,          // 1
+    pub is_disputed: bool,          // 1 - Releases blocked while a dispute is open
     pub is_initialized: bool,       // 1
     pub bump: u8,                   // 1
     pub created_at: i64,            // 8
+    pub response_window: i64,       // 8 - Seconds the tenant has to answer a deduction
+    pub proposed_deduction: u64,    // 8 - Amount the landlord asked to keep
+    pub deduction_reason_hash: [u8; 32], // 32 - Hash of the itemized deduction list
+    pub deduction_deadline: i64,    // 8 - Auto-accept time, 0 = no pending proposal
+    pub lease_end_date: i64,        // 8 - Mirrors the rental agreement's end date
+    pub inspection_window: i64,     // 8 - Seconds after lease end to claim a deduction
+    #[max_len(MAX_CONTRIBUTORS)]
+    pub contributions: Vec<DepositContribution>, // 4 + 5 * 40 - Primary tenant first
+    pub yield_config: Option<YieldConfig>, // 1 + 34 - None = deposit stays in the vault
+    pub invested_amount: u64,       // 8 - Principal currently in the strategy
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct DepositContribution {
+    pub contributor: Pubkey, // 32
+    pub amount: u64,         // 8
+}
+
+/// Yield strategy for the deposit and the tenants' cut of what it earns
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct YieldConfig {
+    pub strategy: Pubkey,      // 32 - Program implementing the yield adapter interface
+    pub tenant_share_bps: u16, // 2 - Rest of the yield goes to the landlord
+}
+
+impl Escrow {
+    /// Primary tenant or landlord, who agreed to the yield strategy when the lease was signed
+    pub fn is_party(&self, key: &Pubkey) -> bool {
+        *key == self.tenant || *key == self.landlord
+    }
+
+    pub fn has_pending_deduction(&self) -> bool {
+        self.deduction_deadline > 0
+    }
+
+    /// When the tenant may reclaim the deposit if no deduction was proposed
+    pub fn refund_available_at(&self) -> i64 {
+        self.lease_end_date + self.inspection_window
+    }
+
+    /// Splits `tenant_amount` pro rata to the deposit contributions, in contribution order.
+    /// Shares follow what each tenant put in, even after yield has grown the deposit.
+    pub fn refund_shares(&self, tenant_amount: u64) -> Vec<u64> {
+        let contributed: u64 = self.contributions.iter().map(|c| c.amount).sum();
+        let last = self.contributions.len().saturating_sub(1);
+        let mut remaining = tenant_amount;
+        self.contributions
+            .iter()
+            .enumerate()
+            .map(|(i, contribution)| {
+                // Last contributor absorbs the rounding dust, or everything if nothing was recorded
+                let share = if i == last {
+                    remaining
+                } else if contributed == 0 {
+                    0
+                } else {
+                    (tenant_amount as u128 * contribution.amount as u128 / contributed as u128) as u64
+                };
+                remaining -= share;
+                share
+            })
+            .collect()
+    }
+
+    /// Splits remaining accounts into co-tenant token accounts, in contribution order,
+    /// and the extra accounts needed by the mint's transfer hook
+    pub fn split_remaining_accounts<'a, 'info>(
+        &self,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> (&'a [AccountInfo<'info>], &'a [AccountInfo<'info>]) {
+        let co_tenants = self.contributions.len().saturating_sub(1);
+        accounts.split_at(co_tenants.min(accounts.len()))
+    }
+}
+
+/// Leading fields of `rental_agreement::RentalAgreement`, read without depending on
+/// that crate (it depends on this one for CPI).
+#[derive(AnchorDeserialize)]
+pub struct AgreementParties {
+    pub property: Pubkey,
+    pub landlord: Pubkey,
+    pub tenant: Pubkey,
+}
+
+impl AgreementParties {
+    pub fn try_from_account(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        let discriminator = hash(b"account:RentalAgreement").to_bytes();
+        require!(
+            data.len() >= 8 && data[..8] == discriminator[..8],
+            EscrowError::InvalidRentalAgreement
+        );
+        Self::deserialize(&mut &data[8..]).map_err(|_| error!(EscrowError::InvalidRentalAgreement))
+    }
 }
 
 #[event]
@@ -290,6 +1539,21 @@ pub struct EscrowCreated {
     pub amount: u64,
 }
 
+#[event]
+pub struct ContributionAdded {
+    pub escrow: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    pub total: u64,
+}
+
+#[event]
+pub struct DepositRefunded {
+    pub escrow: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
 #[event]
 pub struct EscrowReleased {
     pub escrow: Pubkey,
@@ -305,7 +1569,65 @@ pub struct PartialDeduction {
     pub tenant: Pubkey,
     pub landlord_amount: u64,
     pub tenant_amount: u64,
-    pub reason: String,
+    pub reason_hash: [u8; 32],
+}
+
+#[event]
+pub struct DeductionProposed {
+    pub escrow: Pubkey,
+    pub landlord: Pubkey,
+    pub landlord_amount: u64,
+    pub reason_hash: [u8; 32],
+    pub deadline: i64,
+}
+
+#[event]
+pub struct EscrowClosed {
+    pub escrow: Pubkey,
+    pub rental_agreement: Pubkey,
+    pub tenant: Pubkey,
+    pub landlord: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub swept_amount: u64,
+    pub created_at: i64,
+}
+
+#[event]
+pub struct LeaseEndUpdated {
+    pub escrow: Pubkey,
+    pub lease_end_date: i64,
+    pub refund_available_at: i64,
+}
+
+#[event]
+pub struct EscrowFrozen {
+    pub escrow: Pubkey,
+    pub rental_agreement: Pubkey,
+}
+
+#[event]
+pub struct DepositInvested {
+    pub escrow: Pubkey,
+    pub strategy: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DepositDivested {
+    pub escrow: Pubkey,
+    pub returned: u64,
+    pub tenant_yield: u64,
+    pub landlord_yield: u64,
+}
+
+#[event]
+pub struct DisputeSettled {
+    pub escrow: Pubkey,
+    pub landlord: Pubkey,
+    pub tenant: Pubkey,
+    pub landlord_amount: u64,
+    pub tenant_amount: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
@@ -328,4 +1650,159 @@ pub enum EscrowError {
     InsufficientFunds,
     #[msg("Reason is too long (max 500 characters)")]
     ReasonTooLong,
+    #[msg("Token account mint does not match the escrow mint")]
+    InvalidMint,
+    #[msg("Token account is not the escrow vault")]
+    InvalidVault,
+    #[msg("Account is not a rental agreement")]
+    InvalidRentalAgreement,
+    #[msg("Tenant or landlord does not match the rental agreement")]
+    PartyMismatch,
+    #[msg("Escrow is frozen by an open dispute")]
+    EscrowDisputed,
+    #[msg("Escrow is not under dispute")]
+    EscrowNotDisputed,
+    #[msg("Window must be greater than 0")]
+    InvalidWindow,
+    #[msg("A deduction proposal is already pending")]
+    DeductionPending,
+    #[msg("No deduction proposal is pending")]
+    NoPendingDeduction,
+    #[msg("Tenant response deadline has not passed")]
+    DeadlineNotReached,
+    #[msg("Inspection window has closed")]
+    InspectionWindowClosed,
+    #[msg("Inspection window is still open")]
+    InspectionWindowOpen,
+    #[msg("Escrow has not been released yet")]
+    EscrowNotReleased,
+    #[msg("Escrow already holds the maximum number of contributions")]
+    TooManyContributors,
+    #[msg("A co-tenant token account is missing")]
+    MissingCoTenantAccount,
+    #[msg("Co-tenant token account does not belong to the contributor")]
+    InvalidCoTenantAccount,
+    #[msg("Tenant yield share exceeds 100%")]
+    InvalidYieldShare,
+    #[msg("Yield strategies do not support mints with a transfer hook")]
+    YieldUnsupportedMint,
+    #[msg("Escrow has no yield strategy")]
+    YieldNotEnabled,
+    #[msg("Program is not the escrow's yield strategy")]
+    InvalidStrategy,
+    #[msg("Deposit is invested and must be divested first")]
+    DepositInvested,
+    #[msg("Deposit is not invested")]
+    DepositNotInvested,
+    #[msg("Lease has already ended")]
+    LeaseEnded,
+    #[msg("Lease has not ended yet")]
+    LeaseNotEnded,
+    #[msg("Deposit is not needed for settlement yet")]
+    SettlementNotDue,
+    #[msg("Reputation account does not belong to the party")]
+    InvalidReputation,
+    #[msg("Signer is not the tenant or landlord of this escrow")]
+    NotEscrowParty,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEASE_END: i64 = 1_700_000_000;
+    const DAY: i64 = 86_400;
+
+    /// Deposit of 1_000 paid in full by the primary tenant
+    fn escrow() -> Escrow {
+        let tenant = Pubkey::new_unique();
+        Escrow {
+            rental_agreement: Pubkey::new_unique(),
+            tenant,
+            landlord: Pubkey::new_unique(),
+            escrow_token_account: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount: 1_000,
+            is_released: false,
+            is_disputed: false,
+            is_initialized: true,
+            bump: 255,
+            created_at: LEASE_END - 365 * DAY,
+            response_window: 3 * DAY,
+            proposed_deduction: 0,
+            deduction_reason_hash: [0; 32],
+            deduction_deadline: 0,
+            lease_end_date: LEASE_END,
+            inspection_window: 14 * DAY,
+            contributions: vec![DepositContribution {
+                contributor: tenant,
+                amount: 1_000,
+            }],
+            yield_config: None,
+            invested_amount: 0,
+        }
+    }
+
+    fn contribution(amount: u64) -> DepositContribution {
+        DepositContribution {
+            contributor: Pubkey::new_unique(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn refund_shares_follow_contributions() {
+        let mut escrow = escrow();
+        escrow.contributions = vec![contribution(600), contribution(300), contribution(100)];
+
+        assert_eq!(escrow.refund_shares(1_000), vec![600, 300, 100]);
+        // After a 400 deduction
+        assert_eq!(escrow.refund_shares(600), vec![360, 180, 60]);
+    }
+
+    #[test]
+    fn refund_shares_give_rounding_dust_to_the_last_contributor() {
+        let mut escrow = escrow();
+        escrow.contributions = vec![contribution(1), contribution(1), contribution(1)];
+
+        assert_eq!(escrow.refund_shares(100), vec![33, 33, 34]);
+        assert_eq!(escrow.refund_shares(2), vec![0, 0, 2]);
+    }
+
+    #[test]
+    fn refund_shares_go_to_the_last_contributor_when_nothing_was_recorded() {
+        let mut escrow = escrow();
+        escrow.contributions = vec![contribution(0), contribution(0)];
+
+        assert_eq!(escrow.refund_shares(50), vec![0, 50]);
+    }
+
+    #[test]
+    fn refund_shares_split_yield_by_principal() {
+        let mut escrow = escrow();
+        escrow.contributions = vec![contribution(750), contribution(250)];
+
+        // Tenant share of the yield kept in the deposit
+        assert_eq!(escrow.refund_shares(1_040), vec![780, 260]);
+    }
+
+    #[test]
+    fn refund_opens_once_the_inspection_window_lapses() {
+        let mut escrow = escrow();
+        assert_eq!(escrow.refund_available_at(), LEASE_END + 14 * DAY);
+
+        // A renewal pushes the refund back with the lease end
+        escrow.lease_end_date += 30 * DAY;
+        assert_eq!(escrow.refund_available_at(), LEASE_END + 44 * DAY);
+    }
+
+    #[test]
+    fn deduction_is_pending_only_while_it_has_a_deadline() {
+        let mut escrow = escrow();
+        assert!(!escrow.has_pending_deduction());
+
+        escrow.proposed_deduction = 200;
+        escrow.deduction_deadline = LEASE_END + 3 * DAY;
+        assert!(escrow.has_pending_deduction());
+    }
 }
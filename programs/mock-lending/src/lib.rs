@@ -0,0 +1,285 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("GMRWWGBw1virfUokg8EgxnmSeNCzHNCopSqGDHjdKogs");
+
+/// Share-based lending vault for local tests. It implements the escrow yield adapter
+/// interface, and `accrue_interest` stands in for borrowers paying interest.
+#[program]
+pub mod mock_lending {
+    use super::*;
+
+    pub fn initialize_pool(ctx: Context<InitializePool>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+
+        pool.mint = ctx.accounts.mint.key();
+        pool.vault = ctx.accounts.pool_token_account.key();
+        pool.total_shares = 0;
+        pool.bump = ctx.bumps.pool;
+
+        Ok(())
+    }
+
+    /// Positions are opened ahead of time so a PDA depositor never has to pay for one
+    pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+
+        position.pool = ctx.accounts.pool.key();
+        position.depositor = ctx.accounts.depositor.key();
+        position.shares = 0;
+        position.bump = ctx.bumps.position;
+
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, LendingError::InvalidAmount);
+
+        let pool = &mut ctx.accounts.pool;
+        let vault_balance = ctx.accounts.pool_token_account.amount;
+
+        let shares = if pool.total_shares == 0 || vault_balance == 0 {
+            amount
+        } else {
+            (amount as u128 * pool.total_shares as u128 / vault_balance as u128) as u64
+        };
+        require!(shares > 0, LendingError::InvalidAmount);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.depositor_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.depositor.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        pool.total_shares += shares;
+        ctx.accounts.position.shares += shares;
+
+        Ok(())
+    }
+
+    /// Redeems the whole position at the current share price
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let position = &mut ctx.accounts.position;
+
+        require!(position.shares > 0, LendingError::EmptyPosition);
+
+        let amount = (position.shares as u128
+            * ctx.accounts.pool_token_account.amount as u128
+            / pool.total_shares as u128) as u64;
+
+        let seeds = &[b"pool", pool.mint.as_ref(), &[pool.bump]];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.depositor_token_account.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        pool.total_shares -= position.shares;
+        position.shares = 0;
+
+        Ok(())
+    }
+
+    /// Donates tokens to the pool, raising the value of every share
+    pub fn accrue_interest(ctx: Context<AccrueInterest>, amount: u64) -> Result<()> {
+        require!(amount > 0, LendingError::InvalidAmount);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.payer_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool", mint.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [b"position", pool.key().as_ref(), depositor.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: Any wallet or PDA that will deposit into the pool
+    pub depositor: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account order follows the escrow yield adapter interface, pool last
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = depositor,
+        seeds = [b"position", pool.key().as_ref(), depositor.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        token::authority = depositor,
+        token::mint = mint
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = pool.vault @ LendingError::InvalidVault)]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.mint @ LendingError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(mut, seeds = [b"pool", pool.mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Account order follows the escrow yield adapter interface, pool last
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    pub depositor: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = pool,
+        has_one = depositor,
+        seeds = [b"position", pool.key().as_ref(), depositor.key().as_ref()],
+        bump = position.bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(
+        mut,
+        token::authority = depositor,
+        token::mint = mint
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = pool.vault @ LendingError::InvalidVault)]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.mint @ LendingError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(mut, seeds = [b"pool", pool.mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, Pool>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueInterest<'info> {
+    #[account(seeds = [b"pool", pool.mint.as_ref()], bump = pool.bump)]
+    pub pool: Account<'info, Pool>,
+
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        token::authority = payer,
+        token::mint = mint
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = pool.vault @ LendingError::InvalidVault)]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.mint @ LendingError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub mint: Pubkey,      // 32
+    pub vault: Pubkey,     // 32
+    pub total_shares: u64, // 8
+    pub bump: u8,          // 1
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    pub pool: Pubkey,      // 32
+    pub depositor: Pubkey, // 32
+    pub shares: u64,       // 8
+    pub bump: u8,          // 1
+}
+
+#[error_code]
+pub enum LendingError {
+    #[msg("Amount must be greater than 0")]
+    InvalidAmount,
+    #[msg("Position holds no shares")]
+    EmptyPosition,
+    #[msg("Token account is not the pool vault")]
+    InvalidVault,
+    #[msg("Mint does not match the pool")]
+    InvalidMint,
+}
@@ -1,7 +1,43 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
 
 declare_id!("5d3VC6f3bRHUZcos7GdA6fmj8Xtuhf2oSCDD988kmDWs");
 
+/// Rental Agreement program, the only program allowed to change a listing's rental status
+pub mod rental_agreement_program {
+    use super::*;
+
+    declare_id!("FD4wJxjtivZBoujfYACCUNo1D7ygsbn9Gx26cU1UqPXV");
+}
+
+/// Escrow program, which records dispute outcomes in reputations
+pub mod escrow_program {
+    use super::*;
+
+    declare_id!("7aa5fKvc4ejGWZdbxn8DmH5RVhofgwURXibcp3RtstAg");
+}
+
+/// Seed of the rental-agreement PDA that signs status updates
+pub const RENTAL_AUTHORITY_SEED: &[u8] = b"rental_authority";
+
+/// Seed of the escrow PDA that signs reputation updates
+pub const ESCROW_AUTHORITY_SEED: &[u8] = b"escrow_authority";
+
+/// Most open windows or blocked ranges a listing's calendar can hold, each
+pub const MAX_CALENDAR_RANGES: usize = 8;
+
+/// Most leases a listing can have booked at once, current and future
+pub const MAX_BOOKINGS: usize = 4;
+
+/// Score every wallet starts from, out of MAX_REPUTATION_SCORE
+pub const BASE_REPUTATION_SCORE: u16 = 500;
+pub const MAX_REPUTATION_SCORE: u16 = 1_000;
+
+/// Most verifier authorities the registry config can hold
+pub const MAX_VERIFIERS: usize = 10;
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
 pub enum PropertyStatus {
     Available,   // 0 - Can be rented
@@ -9,6 +45,138 @@ pub enum PropertyStatus {
     Deactivated, // 2 - Deactivated by landlord
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum BillingPeriod {
+    Weekly,                  // 0 - Every 7 days
+    Monthly,                 // 1 - Every 30 days
+    Custom { seconds: i64 }, // 2 - Landlord-defined interval
+}
+
+impl BillingPeriod {
+    /// Length of one billing period in seconds
+    pub fn seconds(&self) -> i64 {
+        match self {
+            BillingPeriod::Weekly => 604_800,
+            BillingPeriod::Monthly => 2_592_000,
+            BillingPeriod::Custom { seconds } => *seconds,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.seconds() > 0
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default)]
+pub struct LateFeePolicy {
+    pub grace_period: i64,  // Seconds after the due date before rent is late
+    pub flat_fee: u64,      // Flat fee charged once rent is late
+    pub fee_bps: u16,       // Fee as basis points of rent, charged once rent is late
+    pub default_after: i64, // Seconds after the due date before the lease defaults, 0 = never
+}
+
+impl LateFeePolicy {
+    pub fn is_valid(&self) -> bool {
+        self.grace_period >= 0
+            && self.fee_bps <= 10_000
+            && (self.default_after == 0 || self.default_after > self.grace_period)
+    }
+
+    /// Late fee owed for one missed payment of `rent_amount`
+    pub fn fee_for(&self, rent_amount: u64) -> u64 {
+        let percentage = (rent_amount as u128 * self.fee_bps as u128 / 10_000) as u64;
+        self.flat_fee.saturating_add(percentage)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Default)]
+pub struct TerminationTerms {
+    pub notice_period: i64, // Seconds between giving notice and the lease ending
+    pub break_fee: u64,     // Paid by the party ending the lease early, 0 = none
+}
+
+impl TerminationTerms {
+    pub fn is_valid(&self) -> bool {
+        self.notice_period >= 0
+    }
+}
+
+/// Unix-time range, start inclusive and end exclusive
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct DateRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl DateRange {
+    pub fn is_valid(&self) -> bool {
+        self.start < self.end
+    }
+
+    pub fn overlaps(&self, other: &DateRange) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    pub fn contains(&self, other: &DateRange) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Booking {
+    pub agreement: Pubkey, // Rental agreement holding the dates
+    pub dates: DateRange,  // Lease term reserved on the calendar
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationLevel {
+    Basic, // Documents checked
+    Full,  // Documents checked and property inspected
+}
+
+impl VerificationLevel {
+    /// Value stored in `Property::verification_level`
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            VerificationLevel::Basic => 1,
+            VerificationLevel::Full => 2,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ReputationRole {
+    Tenant,   // 0
+    Landlord, // 1
+}
+
+impl ReputationRole {
+    /// Seed separating a wallet's tenant and landlord reputations
+    pub fn seed(&self) -> &'static [u8] {
+        match self {
+            ReputationRole::Tenant => b"tenant",
+            ReputationRole::Landlord => b"landlord",
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReputationEvent {
+    LeaseCompleted,
+    OnTimePayment,
+    LatePayment,
+    Default,
+    DisputeWon,
+    DisputeLost,
+    DisputeSplit, // Deposit divided evenly, neither party won
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PaymentMode {
+    Periodic,  // 0 - Rent paid once per billing period
+    Streaming, // 1 - Prepaid balance released to the landlord per second
+}
+
 #[program]
 pub mod property_registry {
     use super::*;
@@ -20,6 +188,13 @@ pub mod property_registry {
         deposit_amount: u64,
         lease_duration: i64,
         metadata_uri: String,
+        late_fee_policy: LateFeePolicy,
+        billing_period: BillingPeriod,
+        max_prepaid_periods: u8,
+        termination_terms: TerminationTerms,
+        payment_mode: PaymentMode,
+        min_tenant_score: u16,
+        application_deposit: u64,
     ) -> Result<()> {
         let property = &mut ctx.accounts.property;
 
@@ -27,6 +202,10 @@ pub mod property_registry {
         require!(deposit_amount > 0, PropertyError::InvalidDepositAmount);
         require!(lease_duration > 0, PropertyError::InvalidLeaseDuration);
         require!(metadata_uri.len() <= 200, PropertyError::MetadataUriTooLong);
+        require!(late_fee_policy.is_valid(), PropertyError::InvalidLateFeePolicy);
+        require!(billing_period.is_valid(), PropertyError::InvalidBillingPeriod);
+        require!(termination_terms.is_valid(), PropertyError::InvalidTerminationTerms);
+        require!(min_tenant_score <= MAX_REPUTATION_SCORE, PropertyError::InvalidReputationScore);
 
         property.landlord = ctx.accounts.landlord.key();
         property.property_id = property_id;
@@ -43,6 +222,20 @@ pub mod property_registry {
         property.successful_rentals = 0;
         property.bump = ctx.bumps.property;
         property.created_at = Clock::get()?.unix_timestamp;
+        property.late_fee_policy = late_fee_policy;
+        property.billing_period = billing_period;
+        property.max_prepaid_periods = max_prepaid_periods;
+        property.termination_terms = termination_terms;
+        property.payment_mint = ctx.accounts.payment_mint.key();
+        property.payment_mode = payment_mode;
+        property.verified_by = Pubkey::default();
+        property.verification_requested_at = 0;
+        property.min_tenant_score = min_tenant_score;
+        property.application_deposit = application_deposit;
+        property.open_windows = Vec::new();
+        property.blocked_ranges = Vec::new();
+        property.bookings = Vec::new();
+        property.last_booked_at = 0;
 
         emit!(PropertyCreated {
             property: property.key(),
@@ -50,6 +243,7 @@ pub mod property_registry {
             property_id: property.property_id,
             rent_amount: property.rent_amount,
             deposit_amount: property.deposit_amount,
+            payment_mint: property.payment_mint,
         });
 
         Ok(())
@@ -62,9 +256,23 @@ pub mod property_registry {
         lease_duration: Option<i64>,
         status: Option<PropertyStatus>,
         metadata_uri: Option<String>,
+        late_fee_policy: Option<LateFeePolicy>,
+        billing_period: Option<BillingPeriod>,
+        max_prepaid_periods: Option<u8>,
+        termination_terms: Option<TerminationTerms>,
+        payment_mode: Option<PaymentMode>,
+        min_tenant_score: Option<u16>,
+        application_deposit: Option<u64>,
     ) -> Result<()> {
         let property = &mut ctx.accounts.property;
 
+        // Verification vouches for the listing as attested, so core changes void it
+        let core_changed = rent_amount.is_some()
+            || deposit_amount.is_some()
+            || lease_duration.is_some()
+            || metadata_uri.is_some()
+            || ctx.accounts.payment_mint.is_some();
+
         if let Some(amount) = rent_amount {
             require!(amount > 0, PropertyError::InvalidRentAmount);
             property.rent_amount = amount;
@@ -91,6 +299,55 @@ pub mod property_registry {
             property.metadata_uri = uri;
         }
 
+        if let Some(policy) = late_fee_policy {
+            require!(policy.is_valid(), PropertyError::InvalidLateFeePolicy);
+            property.late_fee_policy = policy;
+        }
+
+        if let Some(period) = billing_period {
+            require!(period.is_valid(), PropertyError::InvalidBillingPeriod);
+            property.billing_period = period;
+        }
+
+        if let Some(periods) = max_prepaid_periods {
+            property.max_prepaid_periods = periods;
+        }
+
+        if let Some(terms) = termination_terms {
+            require!(terms.is_valid(), PropertyError::InvalidTerminationTerms);
+            property.termination_terms = terms;
+        }
+
+        if let Some(mode) = payment_mode {
+            property.payment_mode = mode;
+        }
+
+        if let Some(score) = min_tenant_score {
+            require!(score <= MAX_REPUTATION_SCORE, PropertyError::InvalidReputationScore);
+            property.min_tenant_score = score;
+        }
+
+        // Applications already submitted keep the deposit they paid
+        if let Some(amount) = application_deposit {
+            property.application_deposit = amount;
+        }
+
+        // Existing leases keep the mint they were created with
+        if let Some(mint) = &ctx.accounts.payment_mint {
+            property.payment_mint = mint.key();
+        }
+
+        if core_changed && property.is_verified {
+            property.is_verified = false;
+            property.verification_level = 0;
+            property.verified_by = Pubkey::default();
+
+            emit!(VerificationRevoked {
+                property: property.key(),
+                landlord: property.landlord,
+            });
+        }
+
         emit!(PropertyUpdated {
             property: property.key(),
             landlord: property.landlord,
@@ -99,6 +356,254 @@ pub mod property_registry {
         Ok(())
     }
 
+    /// Replaces the listing's calendar. No open windows means bookable at any time.
+    pub fn set_availability(
+        ctx: Context<SetAvailability>,
+        open_windows: Vec<DateRange>,
+        blocked_ranges: Vec<DateRange>,
+    ) -> Result<()> {
+        let property = &mut ctx.accounts.property;
+
+        require!(
+            open_windows.len() <= MAX_CALENDAR_RANGES && blocked_ranges.len() <= MAX_CALENDAR_RANGES,
+            PropertyError::TooManyCalendarRanges
+        );
+        require!(
+            open_windows.iter().chain(blocked_ranges.iter()).all(DateRange::is_valid),
+            PropertyError::InvalidDateRange
+        );
+        // Dates already let cannot be blocked out from under the tenant
+        require!(
+            !blocked_ranges
+                .iter()
+                .any(|blocked| property.bookings.iter().any(|b| b.dates.overlaps(blocked))),
+            PropertyError::DatesUnavailable
+        );
+
+        property.open_windows = open_windows;
+        property.blocked_ranges = blocked_ranges;
+
+        emit!(AvailabilityUpdated {
+            property: property.key(),
+            landlord: property.landlord,
+            open_windows: property.open_windows.clone(),
+            blocked_ranges: property.blocked_ranges.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn initialize_config(ctx: Context<InitializeConfig>, verifiers: Vec<Pubkey>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(verifiers.len() <= MAX_VERIFIERS, PropertyError::TooManyVerifiers);
+
+        config.admin = ctx.accounts.admin.key();
+        config.verifiers = verifiers;
+        config.bump = ctx.bumps.config;
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            verifiers: config.verifiers.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        admin: Option<Pubkey>,
+        verifiers: Option<Vec<Pubkey>>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        if let Some(new_admin) = admin {
+            config.admin = new_admin;
+        }
+
+        if let Some(new_verifiers) = verifiers {
+            require!(new_verifiers.len() <= MAX_VERIFIERS, PropertyError::TooManyVerifiers);
+            config.verifiers = new_verifiers;
+        }
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            verifiers: config.verifiers.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Landlord submits the hash of their ownership documents for a verifier to review
+    pub fn submit_verification(ctx: Context<SubmitVerification>, document_hash: String) -> Result<()> {
+        let property = &mut ctx.accounts.property;
+
+        require!(!document_hash.is_empty(), PropertyError::MissingDocumentHash);
+        require!(document_hash.len() <= 100, PropertyError::DocumentHashTooLong);
+
+        // New documents need a fresh review
+        property.document_hash = document_hash;
+        property.is_verified = false;
+        property.verification_level = 0;
+        property.verified_by = Pubkey::default();
+        property.verification_requested_at = Clock::get()?.unix_timestamp;
+
+        emit!(VerificationSubmitted {
+            property: property.key(),
+            landlord: property.landlord,
+            document_hash: property.document_hash.clone(),
+        });
+
+        Ok(())
+    }
+
+    pub fn attest_property(ctx: Context<AttestProperty>, level: VerificationLevel) -> Result<()> {
+        let property = &mut ctx.accounts.property;
+        let verifier = ctx.accounts.verifier.key();
+
+        require!(
+            ctx.accounts.config.verifiers.contains(&verifier),
+            PropertyError::NotVerifier
+        );
+        require!(
+            property.verification_requested_at > 0,
+            PropertyError::NoVerificationPending
+        );
+
+        property.is_verified = true;
+        property.verification_level = level.as_u8();
+        property.verified_by = verifier;
+        property.verification_requested_at = 0;
+
+        emit!(PropertyAttested {
+            property: property.key(),
+            verifier,
+            level: property.verification_level,
+            document_hash: property.document_hash.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Anyone may open a wallet's reputation, which starts at the base score. The rental
+    /// program opens them itself when a landlord first approves or a tenant first leases.
+    pub fn init_reputation(ctx: Context<InitReputation>, role: ReputationRole) -> Result<()> {
+        let reputation = &mut ctx.accounts.reputation;
+
+        reputation.wallet = ctx.accounts.wallet.key();
+        reputation.role = role;
+        reputation.leases_completed = 0;
+        reputation.on_time_payments = 0;
+        reputation.late_payments = 0;
+        reputation.defaults = 0;
+        reputation.disputes_won = 0;
+        reputation.disputes_lost = 0;
+        reputation.disputes_split = 0;
+        reputation.score = BASE_REPUTATION_SCORE;
+        reputation.bump = ctx.bumps.reputation;
+        reputation.updated_at = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Called by the rental-agreement and escrow programs as leases play out
+    pub fn record_reputation(ctx: Context<RecordReputation>, event: ReputationEvent) -> Result<()> {
+        let reputation = &mut ctx.accounts.reputation;
+
+        match event {
+            ReputationEvent::LeaseCompleted => reputation.leases_completed += 1,
+            ReputationEvent::OnTimePayment => reputation.on_time_payments += 1,
+            ReputationEvent::LatePayment => reputation.late_payments += 1,
+            ReputationEvent::Default => reputation.defaults += 1,
+            ReputationEvent::DisputeWon => reputation.disputes_won += 1,
+            ReputationEvent::DisputeLost => reputation.disputes_lost += 1,
+            ReputationEvent::DisputeSplit => reputation.disputes_split += 1,
+        }
+        reputation.score = reputation.compute_score();
+        reputation.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(ReputationUpdated {
+            wallet: reputation.wallet,
+            role: reputation.role,
+            event,
+            score: reputation.score,
+        });
+
+        Ok(())
+    }
+
+    /// Books `dates` for a lease, which may start after a current tenancy ends
+    pub fn mark_rented(ctx: Context<MarkRented>, agreement: Pubkey, dates: DateRange) -> Result<()> {
+        let property = &mut ctx.accounts.property;
+
+        property.book(agreement, dates, Clock::get()?.unix_timestamp)?;
+
+        emit!(PropertyRented {
+            property: property.key(),
+            landlord: property.landlord,
+            agreement,
+            dates,
+        });
+
+        Ok(())
+    }
+
+    /// Moves the end of a lease's booking, as when the lease is renewed
+    pub fn extend_booking(ctx: Context<ExtendBooking>, agreement: Pubkey, end: i64) -> Result<()> {
+        let property = &mut ctx.accounts.property;
+
+        let index = property
+            .bookings
+            .iter()
+            .position(|b| b.agreement == agreement)
+            .ok_or(PropertyError::BookingNotFound)?;
+        let dates = DateRange {
+            start: property.bookings[index].dates.start,
+            end,
+        };
+        require!(dates.is_valid(), PropertyError::InvalidDateRange);
+        require!(
+            property.is_bookable(&dates, Some(&agreement)),
+            PropertyError::DatesUnavailable
+        );
+
+        property.bookings[index].dates = dates;
+
+        emit!(BookingExtended {
+            property: property.key(),
+            agreement,
+            dates,
+        });
+
+        Ok(())
+    }
+
+    pub fn mark_vacated(ctx: Context<MarkVacated>, agreement: Pubkey, completed: bool) -> Result<()> {
+        let property = &mut ctx.accounts.property;
+
+        property.bookings.retain(|b| b.agreement != agreement);
+
+        // A listing the landlord deactivated mid-lease stays deactivated, and one
+        // pre-let to the next tenant stays rented
+        if property.status == PropertyStatus::Rented && property.bookings.is_empty() {
+            property.status = PropertyStatus::Available;
+            property.is_available = true;
+        }
+
+        property.total_rentals += 1;
+        if completed {
+            property.successful_rentals += 1;
+        }
+
+        emit!(PropertyVacated {
+            property: property.key(),
+            landlord: property.landlord,
+            completed,
+        });
+
+        Ok(())
+    }
+
     pub fn deactivate_listing(ctx: Context<DeactivateListing>) -> Result<()> {
         let property = &mut ctx.accounts.property;
         property.status = PropertyStatus::Deactivated;
@@ -128,6 +633,9 @@ pub struct CreateListing<'info> {
     #[account(mut)]
     pub landlord: Signer<'info>,
 
+    /// Token rent and deposit are paid in, SPL Token or Token-2022
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -142,6 +650,9 @@ pub struct UpdateListing<'info> {
     pub property: Account<'info, Property>,
 
     pub landlord: Signer<'info>,
+
+    /// New payment mint, if it changes
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
 }
 
 #[derive(Accounts)]
@@ -157,12 +668,201 @@ pub struct DeactivateListing<'info> {
     pub landlord: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetAvailability<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"property", landlord.key().as_ref(), property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    pub landlord: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub program: Program<'info, crate::program::PropertyRegistry>,
+
+    #[account(
+        constraint = is_upgrade_authority(&program, &program_data, admin.key)? @ PropertyError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Whether `admin` holds `program`'s upgrade authority. Config accounts can only be claimed
+/// this way, so nobody can race the deployer to a freshly deployed program's settings.
+pub fn is_upgrade_authority<T>(
+    program: &Program<T>,
+    program_data: &Account<ProgramData>,
+    admin: &Pubkey,
+) -> Result<bool> {
+    Ok(program.programdata_address()? == Some(program_data.key())
+        && program_data.upgrade_authority_address == Some(*admin))
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        has_one = admin @ PropertyError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitVerification<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"property", landlord.key().as_ref(), property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    pub landlord: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestProperty<'info> {
+    #[account(
+        mut,
+        seeds = [b"property", property.landlord.as_ref(), property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub verifier: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role: ReputationRole)]
+pub struct InitReputation<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Reputation::INIT_SPACE,
+        seeds = [b"reputation", role.seed(), wallet.key().as_ref()],
+        bump
+    )]
+    pub reputation: Account<'info, Reputation>,
+
+    /// CHECK: Wallet whose rental history this tracks
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"reputation", reputation.role.seed(), reputation.wallet.as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Account<'info, Reputation>,
+
+    /// Registry authority PDA of the rental-agreement or escrow program
+    #[account(
+        constraint = is_reputation_authority(authority.key) @ PropertyError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+}
+
+/// Whether `key` is a PDA allowed to update reputations
+fn is_reputation_authority(key: &Pubkey) -> bool {
+    let (rental_authority, _) =
+        Pubkey::find_program_address(&[RENTAL_AUTHORITY_SEED], &rental_agreement_program::ID);
+    let (escrow_authority, _) =
+        Pubkey::find_program_address(&[ESCROW_AUTHORITY_SEED], &escrow_program::ID);
+    *key == rental_authority || *key == escrow_authority
+}
+
+#[derive(Accounts)]
+pub struct MarkRented<'info> {
+    #[account(
+        mut,
+        seeds = [b"property", property.landlord.as_ref(), property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump,
+        seeds::program = rental_agreement_program::ID
+    )]
+    pub rental_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendBooking<'info> {
+    #[account(
+        mut,
+        seeds = [b"property", property.landlord.as_ref(), property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump,
+        seeds::program = rental_agreement_program::ID
+    )]
+    pub rental_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarkVacated<'info> {
+    #[account(
+        mut,
+        seeds = [b"property", property.landlord.as_ref(), property.property_id.to_le_bytes().as_ref()],
+        bump = property.bump
+    )]
+    pub property: Account<'info, Property>,
+
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump,
+        seeds::program = rental_agreement_program::ID
+    )]
+    pub rental_authority: Signer<'info>,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Property {
     pub landlord: Pubkey,           // 32
     pub property_id: u64,           // 8
-    pub rent_amount: u64,           // 8 (in lamports/smallest unit of USDC)
+    pub rent_amount: u64,           // 8 (in smallest unit of the payment mint)
     pub deposit_amount: u64,        // 8
     pub lease_duration: i64,        // 8 (in seconds)
     pub status: PropertyStatus,     // 1 + 1 - Enum status
@@ -177,6 +877,95 @@ pub struct Property {
     pub successful_rentals: u32,    // 4 - Track successful completions
     pub bump: u8,                   // 1
     pub created_at: i64,            // 8
+    pub late_fee_policy: LateFeePolicy, // 8 + 8 + 2 + 8 - Copied into each lease
+    pub billing_period: BillingPeriod, // 1 + 8 - Rent schedule interval
+    pub max_prepaid_periods: u8,    // 1 - How many periods ahead rent may be paid
+    pub termination_terms: TerminationTerms, // 8 + 8 - Early termination notice and fee
+    pub payment_mint: Pubkey,       // 32 - Token accepted for rent and deposit
+    pub payment_mode: PaymentMode,  // 1 - Periodic payments or a per-second stream
+    pub verified_by: Pubkey,        // 32 - Verifier behind the current attestation
+    pub verification_requested_at: i64, // 8 - When documents were submitted, 0 = no review pending
+    pub min_tenant_score: u16,      // 2 - Lowest tenant reputation score accepted, 0 = anyone
+    pub application_deposit: u64,   // 8 - Refundable deposit to apply, 0 = none
+    #[max_len(MAX_CALENDAR_RANGES)]
+    pub open_windows: Vec<DateRange>, // 4 + 8 * 16 - Bookable ranges, empty = any time
+    #[max_len(MAX_CALENDAR_RANGES)]
+    pub blocked_ranges: Vec<DateRange>, // 4 + 8 * 16 - Ranges the landlord has closed
+    #[max_len(MAX_BOOKINGS)]
+    pub bookings: Vec<Booking>,     // 4 + 4 * 48 - Current and future leases
+    pub last_booked_at: i64,        // 8 - When the latest lease was booked
+}
+
+impl Property {
+    /// Whether `dates` fit an open window and clear every blocked range and booking,
+    /// other than the booking held by `except`
+    pub fn is_bookable(&self, dates: &DateRange, except: Option<&Pubkey>) -> bool {
+        let in_window =
+            self.open_windows.is_empty() || self.open_windows.iter().any(|w| w.contains(dates));
+        let blocked = self.blocked_ranges.iter().any(|b| b.overlaps(dates));
+        let booked = self
+            .bookings
+            .iter()
+            .any(|b| Some(&b.agreement) != except && b.dates.overlaps(dates));
+        in_window && !blocked && !booked
+    }
+
+    /// Reserves `dates` for `agreement` if the calendar allows it
+    pub fn book(&mut self, agreement: Pubkey, dates: DateRange, now: i64) -> Result<()> {
+        require!(
+            self.status != PropertyStatus::Deactivated,
+            PropertyError::PropertyNotAvailable
+        );
+        require!(dates.is_valid(), PropertyError::InvalidDateRange);
+        require!(self.is_bookable(&dates, None), PropertyError::DatesUnavailable);
+        require!(self.bookings.len() < MAX_BOOKINGS, PropertyError::TooManyBookings);
+
+        self.bookings.push(Booking { agreement, dates });
+        self.status = PropertyStatus::Rented;
+        self.is_available = false;
+        self.last_booked_at = now;
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Reputation {
+    pub wallet: Pubkey,             // 32
+    pub role: ReputationRole,       // 1
+    pub leases_completed: u32,      // 4
+    pub on_time_payments: u32,      // 4
+    pub late_payments: u32,         // 4
+    pub defaults: u32,              // 4
+    pub disputes_won: u32,          // 4
+    pub disputes_lost: u32,         // 4
+    pub score: u16,                 // 2 - 0 to MAX_REPUTATION_SCORE
+    pub bump: u8,                   // 1
+    pub updated_at: i64,            // 8
+    pub disputes_split: u32,        // 4 - Resolved without a winner, score unaffected
+}
+
+impl Reputation {
+    /// Base score moved up by good history and down by bad, weighted by severity
+    pub fn compute_score(&self) -> u16 {
+        let gains = self.leases_completed as i64 * 25
+            + self.on_time_payments as i64 * 5
+            + self.disputes_won as i64 * 10;
+        let losses = self.late_payments as i64 * 15
+            + self.defaults as i64 * 150
+            + self.disputes_lost as i64 * 30;
+        (BASE_REPUTATION_SCORE as i64 + gains - losses).clamp(0, MAX_REPUTATION_SCORE as i64) as u16
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,              // 32 - May update the config
+    #[max_len(MAX_VERIFIERS)]
+    pub verifiers: Vec<Pubkey>,     // 4 + 10 * 32 - Authorities allowed to attest properties
+    pub bump: u8,                   // 1
 }
 
 #[event]
@@ -186,6 +975,7 @@ pub struct PropertyCreated {
     pub property_id: u64,
     pub rent_amount: u64,
     pub deposit_amount: u64,
+    pub payment_mint: Pubkey,
 }
 
 #[event]
@@ -194,6 +984,71 @@ pub struct PropertyUpdated {
     pub landlord: Pubkey,
 }
 
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub verifiers: Vec<Pubkey>,
+}
+
+#[event]
+pub struct VerificationSubmitted {
+    pub property: Pubkey,
+    pub landlord: Pubkey,
+    pub document_hash: String,
+}
+
+#[event]
+pub struct PropertyAttested {
+    pub property: Pubkey,
+    pub verifier: Pubkey,
+    pub level: u8,
+    pub document_hash: String,
+}
+
+#[event]
+pub struct VerificationRevoked {
+    pub property: Pubkey,
+    pub landlord: Pubkey,
+}
+
+#[event]
+pub struct ReputationUpdated {
+    pub wallet: Pubkey,
+    pub role: ReputationRole,
+    pub event: ReputationEvent,
+    pub score: u16,
+}
+
+#[event]
+pub struct AvailabilityUpdated {
+    pub property: Pubkey,
+    pub landlord: Pubkey,
+    pub open_windows: Vec<DateRange>,
+    pub blocked_ranges: Vec<DateRange>,
+}
+
+#[event]
+pub struct PropertyRented {
+    pub property: Pubkey,
+    pub landlord: Pubkey,
+    pub agreement: Pubkey,
+    pub dates: DateRange,
+}
+
+#[event]
+pub struct BookingExtended {
+    pub property: Pubkey,
+    pub agreement: Pubkey,
+    pub dates: DateRange,
+}
+
+#[event]
+pub struct PropertyVacated {
+    pub property: Pubkey,
+    pub landlord: Pubkey,
+    pub completed: bool,
+}
+
 #[event]
 pub struct PropertyDeactivated {
     pub property: Pubkey,
@@ -210,4 +1065,170 @@ pub enum PropertyError {
     InvalidLeaseDuration,
     #[msg("Metadata URI is too long (max 200 characters)")]
     MetadataUriTooLong,
+    #[msg("Property is not available for rent")]
+    PropertyNotAvailable,
+    #[msg("Late fee policy is invalid")]
+    InvalidLateFeePolicy,
+    #[msg("Billing period must be greater than 0")]
+    InvalidBillingPeriod,
+    #[msg("Notice period cannot be negative")]
+    InvalidTerminationTerms,
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Too many verifiers (max 10)")]
+    TooManyVerifiers,
+    #[msg("Signer is not a registered verifier")]
+    NotVerifier,
+    #[msg("Document hash is required")]
+    MissingDocumentHash,
+    #[msg("Document hash is too long (max 100 characters)")]
+    DocumentHashTooLong,
+    #[msg("No verification request is pending")]
+    NoVerificationPending,
+    #[msg("Reputation score is out of range")]
+    InvalidReputationScore,
+    #[msg("Date range must end after it starts")]
+    InvalidDateRange,
+    #[msg("Too many calendar ranges (max 8 each)")]
+    TooManyCalendarRanges,
+    #[msg("Requested dates are not available")]
+    DatesUnavailable,
+    #[msg("Property has too many bookings")]
+    TooManyBookings,
+    #[msg("Lease has no booking on this property")]
+    BookingNotFound,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: i64, end: i64) -> DateRange {
+        DateRange { start, end }
+    }
+
+    fn property() -> Property {
+        Property {
+            landlord: Pubkey::new_unique(),
+            property_id: 1,
+            rent_amount: 1_000,
+            deposit_amount: 2_000,
+            lease_duration: 100,
+            status: PropertyStatus::Available,
+            is_available: true,
+            is_verified: false,
+            verification_level: 0,
+            metadata_uri: String::new(),
+            document_hash: String::new(),
+            total_rentals: 0,
+            successful_rentals: 0,
+            bump: 255,
+            created_at: 0,
+            late_fee_policy: LateFeePolicy::default(),
+            billing_period: BillingPeriod::Monthly,
+            max_prepaid_periods: 1,
+            termination_terms: TerminationTerms::default(),
+            payment_mint: Pubkey::new_unique(),
+            payment_mode: PaymentMode::Periodic,
+            verified_by: Pubkey::default(),
+            verification_requested_at: 0,
+            min_tenant_score: 0,
+            application_deposit: 0,
+            open_windows: Vec::new(),
+            blocked_ranges: Vec::new(),
+            bookings: Vec::new(),
+            last_booked_at: 0,
+        }
+    }
+
+    #[test]
+    fn date_ranges_are_end_exclusive() {
+        let lease = range(100, 200);
+
+        assert!(lease.overlaps(&range(150, 250)));
+        assert!(lease.overlaps(&range(0, 101)));
+        assert!(lease.overlaps(&range(120, 180)));
+        // Back-to-back leases share a boundary without overlapping
+        assert!(!lease.overlaps(&range(200, 300)));
+        assert!(!lease.overlaps(&range(0, 100)));
+    }
+
+    #[test]
+    fn date_range_contains_its_own_bounds() {
+        let window = range(100, 200);
+
+        assert!(window.contains(&range(100, 200)));
+        assert!(window.contains(&range(120, 180)));
+        assert!(!window.contains(&range(90, 180)));
+        assert!(!window.contains(&range(120, 201)));
+        assert!(!range(200, 200).is_valid());
+    }
+
+    #[test]
+    fn bookable_any_time_without_open_windows() {
+        let property = property();
+
+        assert!(property.is_bookable(&range(100, 200), None));
+    }
+
+    #[test]
+    fn bookable_only_inside_an_open_window() {
+        let mut property = property();
+        property.open_windows = vec![range(0, 100), range(300, 500)];
+
+        assert!(property.is_bookable(&range(300, 400), None));
+        assert!(!property.is_bookable(&range(50, 150), None));
+        // Spanning two windows is not enough, one must hold the whole range
+        assert!(!property.is_bookable(&range(0, 500), None));
+    }
+
+    #[test]
+    fn blocked_ranges_and_bookings_are_unavailable() {
+        let mut property = property();
+        let agreement = Pubkey::new_unique();
+        property.blocked_ranges = vec![range(400, 500)];
+        property.bookings = vec![Booking {
+            agreement,
+            dates: range(100, 200),
+        }];
+
+        assert!(!property.is_bookable(&range(450, 600), None));
+        assert!(!property.is_bookable(&range(150, 250), None));
+        assert!(property.is_bookable(&range(200, 400), None));
+    }
+
+    #[test]
+    fn booking_can_extend_over_its_own_dates() {
+        let mut property = property();
+        let agreement = Pubkey::new_unique();
+        property.bookings = vec![
+            Booking {
+                agreement,
+                dates: range(100, 200),
+            },
+            Booking {
+                agreement: Pubkey::new_unique(),
+                dates: range(300, 400),
+            },
+        ];
+
+        assert!(property.is_bookable(&range(100, 300), Some(&agreement)));
+        // Still cannot run into the next tenant's lease
+        assert!(!property.is_bookable(&range(100, 350), Some(&agreement)));
+    }
+
+    #[test]
+    fn booking_reserves_the_dates_and_rents_the_property() {
+        let mut property = property();
+        let agreement = Pubkey::new_unique();
+
+        property.book(agreement, range(100, 200), 50).unwrap();
+        assert!(property.status == PropertyStatus::Rented);
+        assert_eq!(property.last_booked_at, 50);
+
+        // A pre-let for later dates books alongside the current lease
+        assert!(property.book(Pubkey::new_unique(), range(200, 300), 60).is_ok());
+        assert!(property.book(Pubkey::new_unique(), range(150, 250), 70).is_err());
+        assert_eq!(property.bookings.len(), 2);
+    }
 }
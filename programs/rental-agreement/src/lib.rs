@@ -1,112 +1,236 @@
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer as SolTransfer};
-use anchor_spl::token::{self, Token, TokenAccount, Transfer as TokenTransfer};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    self, Approve, CloseAccount, Mint, Revoke, TokenAccount, TokenInterface, TransferChecked,
+};
+use escrow::program::Escrow as EscrowProgram;
+use escrow::{harvest_withheld_fees, transfer_checked_with_hook, YieldConfig};
+use property_registry::program::PropertyRegistry;
+use property_registry::{
+    is_upgrade_authority, BillingPeriod, DateRange, LateFeePolicy, PaymentMode, Property,
+    PropertyStatus, Reputation, ReputationEvent, ReputationRole, TerminationTerms,
+    RENTAL_AUTHORITY_SEED,
+};
 
 declare_id!("FD4wJxjtivZBoujfYACCUNo1D7ygsbn9Gx26cU1UqPXV");
 
-// Property Registry program ID
-const PROPERTY_REGISTRY_ID: &str = "5d3VC6f3bRHUZcos7GdA6fmj8Xtuhf2oSCDD988kmDWs";
+/// Highest platform fee the admin can set, 10%
+pub const MAX_FEE_BPS: u16 = 1_000;
 
-#[derive(Clone)]
-pub struct PropertyProgram;
+/// Primary tenant plus co-tenants, bounded by what the escrow can refund
+pub const MAX_TENANTS: u8 = escrow::MAX_CONTRIBUTORS;
 
-impl anchor_lang::Id for PropertyProgram {
-    fn id() -> Pubkey {
-        Pubkey::from_str(PROPERTY_REGISTRY_ID).unwrap()
-    }
+/// On-time payments a single lease can credit to the tenant's reputation
+pub const MAX_CREDITED_PAYMENTS: u32 = 12;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct TenantShare {
+    pub tenant: Pubkey,  // Tenant liable for this share
+    pub share_bps: u16,  // Share of rent and deposit in basis points
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Guarantee {
+    pub token_account: Pubkey, // Guarantor account the agreement PDA may draw from
+    pub cap: u64,              // Most the guarantor can ever be charged
+    pub drawn: u64,            // Charged so far
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct Autopay {
+    pub payer: Pubkey,         // Tenant who set it up
+    pub token_account: Pubkey, // Account the agreement PDA collects rent from
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct RenewalTerms {
+    pub rent_amount: u64,    // Rent once the current term ends
+    pub lease_duration: i64, // Seconds added to the current end date
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct ScheduledRent {
+    pub rent_amount: u64,  // Renewed rent
+    pub effective_at: i64, // End of the term it was renewed from
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct TerminationNotice {
+    pub given_by: Pubkey,    // Party ending the lease early
+    pub effective_date: i64, // When the lease can be finalized
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DisputeStatus {
+    Open,              // 0 - Raised, escrow frozen
+    EvidenceSubmitted, // 1 - At least one party has submitted evidence
+    Resolved,          // 2 - Settled by the arbitrator
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum PropertyStatus {
-    Available,   // 0 - Can be rented
-    Rented,      // 1 - Currently rented with active lease
-    Deactivated, // 2 - Deactivated by landlord
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ApplicationStatus {
+    Pending,   // 0 - Awaiting the landlord
+    Approved,  // 1 - May be turned into a lease until it expires
+    Rejected,  // 2 - Declined by the landlord
+    Leased,    // 3 - Used to create a lease
+    Withdrawn, // 4 - Withdrawn by the tenant
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct UpdateListingArgs {
-    pub rent_amount: Option<u64>,
-    pub deposit_amount: Option<u64>,
-    pub lease_duration: Option<i64>,
-    pub status: Option<PropertyStatus>,
-    pub metadata_uri: Option<String>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PaymentStatus {
+    Current,   // 0 - Rent paid up
+    Late,      // 1 - Past due beyond the grace period
+    Defaulted, // 2 - Past due beyond the default threshold
 }
 
 #[program]
 pub mod rental_agreement {
     use super::*;
 
-    pub fn create_lease(
-        ctx: Context<CreateLease>,
-        rent_amount: u64,
-        deposit_amount: u64,
-        lease_duration: i64,
+    /// Co-tenants sign too: each one's signer and token account follow as remaining accounts,
+    /// in the order of `co_tenants`, then any accounts the mint's transfer hook needs.
+    pub fn create_lease<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateLease<'info>>,
+        co_tenants: Vec<TenantShare>,
+        guarantor: Option<Pubkey>,
+        start_date: Option<i64>,
     ) -> Result<()> {
         let agreement = &mut ctx.accounts.rental_agreement;
+        let property = &ctx.accounts.property;
         let clock = Clock::get()?;
+        let tenant_key = ctx.accounts.tenant.key();
 
-        require!(rent_amount > 0, RentalError::InvalidRentAmount);
-        require!(deposit_amount > 0, RentalError::InvalidDepositAmount);
-        require!(lease_duration > 0, RentalError::InvalidLeaseDuration);
+        // Leasing one's own listing would only farm reputation
+        require_keys_neq!(tenant_key, property.landlord, RentalError::SelfLease);
+
+        // A rented unit can still be pre-let for dates after the current tenancy;
+        // the registry checks the requested dates against its calendar
+        require!(
+            property.status != PropertyStatus::Deactivated,
+            RentalError::PropertyNotAvailable
+        );
+        let start_date = start_date.unwrap_or(clock.unix_timestamp);
+        require!(start_date >= clock.unix_timestamp, RentalError::InvalidStartDate);
+
+        // A first-time tenant's reputation is opened at the base score, at their expense
+        open_reputation(
+            ctx.accounts.tenant_reputation.to_account_info(),
+            ctx.accounts.tenant.to_account_info(),
+            ctx.accounts.tenant.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.property_program.to_account_info(),
+            ReputationRole::Tenant,
+        )?;
+        let tenant_reputation =
+            Reputation::try_deserialize(&mut &ctx.accounts.tenant_reputation.try_borrow_data()?[..])?;
+        require!(
+            tenant_reputation.score >= property.min_tenant_score,
+            RentalError::ReputationTooLow
+        );
+        require!(
+            ctx.accounts.application.status == ApplicationStatus::Approved,
+            RentalError::ApplicationNotApproved
+        );
+        require!(
+            clock.unix_timestamp < ctx.accounts.application.approval_expires_at,
+            RentalError::ApprovalExpired
+        );
+        require!(
+            co_tenants.len() < MAX_TENANTS as usize,
+            RentalError::TooManyTenants
+        );
+        require!(
+            ctx.remaining_accounts.len() >= co_tenants.len() * 2,
+            RentalError::CoTenantAccountMismatch
+        );
+        let (co_tenant_accounts, hook_accounts) =
+            ctx.remaining_accounts.split_at(co_tenants.len() * 2);
+
+        // The primary tenant covers whatever share the co-tenants don't
+        let mut co_tenant_bps: u32 = 0;
+        for (i, share) in co_tenants.iter().enumerate() {
+            require!(share.share_bps > 0, RentalError::InvalidTenantShare);
+            require!(
+                share.tenant != tenant_key && !co_tenants[..i].iter().any(|s| s.tenant == share.tenant),
+                RentalError::DuplicateTenant
+            );
+            co_tenant_bps += share.share_bps as u32;
+        }
+        require!(co_tenant_bps < 10_000, RentalError::InvalidTenantShare);
 
-        agreement.property = ctx.accounts.property.key();
-        agreement.landlord = ctx.accounts.landlord.key();
-        agreement.tenant = ctx.accounts.tenant.key();
-        agreement.start_date = clock.unix_timestamp;
-        agreement.end_date = clock.unix_timestamp + lease_duration;
-        agreement.rent_amount = rent_amount;
-        agreement.deposit_amount = deposit_amount;
+        if let Some(guarantor) = guarantor {
+            require!(
+                guarantor != tenant_key
+                    && guarantor != property.landlord
+                    && !co_tenants.iter().any(|s| s.tenant == guarantor),
+                RentalError::InvalidGuarantor
+            );
+        }
+
+        // Lease terms always come from the listing, never from the tenant
+        agreement.property = property.key();
+        agreement.landlord = property.landlord;
+        agreement.tenant = tenant_key;
+        agreement.start_date = start_date;
+        agreement.end_date = start_date + property.lease_duration;
+        agreement.rent_amount = property.rent_amount;
+        agreement.deposit_amount = property.deposit_amount;
+        agreement.escrow = ctx.accounts.escrow.key();
         agreement.payment_count = 0;
         agreement.is_active = true;
         agreement.last_payment_date = 0;
         agreement.last_payment_amount = 0;
         agreement.total_paid = 0;
-        agreement.payment_status = 0; // Current
+        agreement.payment_status = PaymentStatus::Current;
         agreement.bump = ctx.bumps.rental_agreement;
+        agreement.late_fee_policy = property.late_fee_policy;
+        agreement.accrued_late_fee = 0;
+        agreement.billing_anchor = agreement.start_date;
+        agreement.billing_period = property.billing_period;
+        agreement.max_prepaid_periods = property.max_prepaid_periods;
+        agreement.periods_paid = 0;
+        agreement.next_due_date = agreement.billing_anchor;
+        agreement.pending_renewal = None;
+        agreement.scheduled_rent = None;
+        agreement.termination_terms = property.termination_terms;
+        agreement.termination_notice = None;
+        agreement.tenant_shares = vec![TenantShare {
+            tenant: tenant_key,
+            share_bps: (10_000 - co_tenant_bps) as u16,
+        }];
+        agreement.tenant_shares.extend(co_tenants.iter().copied());
+        agreement.guarantor = guarantor;
+        agreement.period_collected = 0;
+        agreement.guarantee = None;
+        agreement.payment_mint = property.payment_mint;
+        agreement.autopay = None;
+        agreement.payment_mode = property.payment_mode;
+        agreement.stream_accrued = 0;
+        agreement.stream_checkpoint = agreement.start_date;
+        agreement.stream_open = false;
 
-        // Update property status to Rented through CPI
-        let cpi_program = ctx.accounts.property_program.to_account_info();
-        let cpi_accounts = vec![
-            ctx.accounts.property.to_account_info(),
-            ctx.accounts.landlord.to_account_info(),
-        ];
-
-        // Build instruction data manually for update_listing
-        let mut ix_data = vec![
-            // Instruction discriminator for update_listing (first 8 bytes)
-            // This needs to match the discriminator in property-registry
-            0x35, 0x28, 0xb5, 0xd8, 0x8e, 0x99, 0x4a, 0x3d,
-        ];
+        // One lease per approval; the application deposit is now refundable
+        ctx.accounts.application.status = ApplicationStatus::Leased;
 
-        // Serialize the UpdateListingArgs
-        let args = UpdateListingArgs {
-            rent_amount: None,
-            deposit_amount: None,
-            lease_duration: None,
-            status: Some(PropertyStatus::Rented),
-            metadata_uri: None,
-        };
-        args.serialize(&mut ix_data)?;
-
-        let update_ix = anchor_lang::solana_program::instruction::Instruction {
-            program_id: PropertyProgram::id(),
-            accounts: vec![
-                anchor_lang::solana_program::instruction::AccountMeta::new(
-                    ctx.accounts.property.key(),
-                    false,
-                ),
-                anchor_lang::solana_program::instruction::AccountMeta::new_readonly(
-                    ctx.accounts.landlord.key(),
-                    true,
-                ),
-            ],
-            data: ix_data,
+        // Book the lease term on the property's calendar, signed by this program's registry authority
+        let dates = DateRange {
+            start: agreement.start_date,
+            end: agreement.end_date,
         };
+        let authority_seeds = &[RENTAL_AUTHORITY_SEED, &[ctx.bumps.rental_authority]];
+        let signer = &[&authority_seeds[..]];
 
-        anchor_lang::solana_program::program::invoke(
-            &update_ix,
-            &cpi_accounts,
-        )?;
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.property_program.to_account_info(),
+            property_registry::cpi::accounts::MarkRented {
+                property: ctx.accounts.property.to_account_info(),
+                rental_authority: ctx.accounts.rental_authority.to_account_info(),
+            },
+            signer,
+        );
+        property_registry::cpi::mark_rented(cpi_ctx, agreement.key(), dates)?;
 
         emit!(LeaseCreated {
             agreement: agreement.key(),
@@ -115,185 +239,3066 @@ pub mod rental_agreement {
             tenant: agreement.tenant,
             rent_amount: agreement.rent_amount,
             deposit_amount: agreement.deposit_amount,
+            co_tenants: co_tenants.clone(),
+            guarantor,
+        });
+
+        // Each co-tenant funds their share of the deposit, the primary tenant the rest
+        let co_tenant_deposits: Vec<u64> = co_tenants
+            .iter()
+            .map(|s| (agreement.deposit_amount as u128 * s.share_bps as u128 / 10_000) as u64)
+            .collect();
+        let tenant_deposit = agreement.deposit_amount - co_tenant_deposits.iter().sum::<u64>();
+
+        // Deposit yield is offered by the landlord at approval, and only through the
+        // strategy the platform approved; the tenant accepts it by signing the lease
+        let yield_config = match ctx.accounts.application.tenant_yield_share_bps {
+            Some(tenant_share_bps) => {
+                let strategy = ctx.accounts.config.yield_strategy;
+                require!(strategy != Pubkey::default(), RentalError::YieldNotSupported);
+                Some(YieldConfig {
+                    strategy,
+                    tenant_share_bps,
+                })
+            }
+            None => None,
+        };
+
+        // Persist the agreement so escrow can read the parties during the CPI
+        let end_date = agreement.end_date;
+        let bump = agreement.bump;
+        ctx.accounts.rental_agreement.exit(&crate::ID)?;
+
+        // Fund the security deposit, signed by the agreement PDA
+        let property_key = ctx.accounts.property.key();
+        let agreement_seeds = &[
+            b"rental",
+            property_key.as_ref(),
+            tenant_key.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&agreement_seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.escrow_program.to_account_info(),
+            escrow::cpi::accounts::DepositToEscrow {
+                escrow: ctx.accounts.escrow.to_account_info(),
+                rental_agreement: ctx.accounts.rental_agreement.to_account_info(),
+                tenant: ctx.accounts.tenant.to_account_info(),
+                landlord: ctx.accounts.landlord.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                tenant_token_account: ctx.accounts.tenant_token_account.to_account_info(),
+                escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(hook_accounts.to_vec());
+        escrow::cpi::deposit_to_escrow(
+            cpi_ctx,
+            tenant_deposit,
+            ctx.accounts.config.deduction_response_window,
+            end_date,
+            ctx.accounts.config.inspection_window,
+            yield_config,
+        )?;
+
+        for (i, amount) in co_tenant_deposits.into_iter().enumerate() {
+            let co_tenant = &co_tenant_accounts[i * 2];
+            let co_tenant_token_account = &co_tenant_accounts[i * 2 + 1];
+            require_keys_eq!(
+                co_tenant.key(),
+                co_tenants[i].tenant,
+                RentalError::CoTenantAccountMismatch
+            );
+            require!(amount > 0, RentalError::InvalidTenantShare);
+
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.escrow_program.to_account_info(),
+                escrow::cpi::accounts::AddContribution {
+                    escrow: ctx.accounts.escrow.to_account_info(),
+                    rental_agreement: ctx.accounts.rental_agreement.to_account_info(),
+                    contributor: co_tenant.clone(),
+                    contributor_token_account: co_tenant_token_account.clone(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(hook_accounts.to_vec());
+            escrow::cpi::add_contribution(cpi_ctx, amount)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn submit_application<'info>(
+        ctx: Context<'_, '_, '_, 'info, SubmitApplication<'info>>,
+        profile_hash: [u8; 32],
+    ) -> Result<()> {
+        let property = &ctx.accounts.property;
+
+        require!(
+            property.status != PropertyStatus::Deactivated,
+            RentalError::PropertyNotAvailable
+        );
+
+        // Hold the listing's application deposit until the application is settled
+        let deposit = property.application_deposit;
+        if deposit > 0 {
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.tenant_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.application_vault.to_account_info(),
+                    authority: ctx.accounts.tenant.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_ctx, deposit, ctx.accounts.mint.decimals)?;
+            ctx.accounts.application_vault.reload()?;
+        }
+
+        let application = &mut ctx.accounts.application;
+        application.property = property.key();
+        application.landlord = property.landlord;
+        application.tenant = ctx.accounts.tenant.key();
+        application.profile_hash = profile_hash;
+        application.mint = property.payment_mint;
+        application.deposit_amount = ctx.accounts.application_vault.amount;
+        application.status = ApplicationStatus::Pending;
+        application.tenant_yield_share_bps = None;
+        application.approval_expires_at = 0;
+        application.created_at = Clock::get()?.unix_timestamp;
+        application.bump = ctx.bumps.application;
+
+        emit!(ApplicationSubmitted {
+            application: application.key(),
+            property: application.property,
+            tenant: application.tenant,
+            profile_hash,
+            deposit_amount: application.deposit_amount,
+        });
+
+        Ok(())
+    }
+
+    /// An approval may offer to invest the security deposit, giving the tenant
+    /// `tenant_yield_share_bps` of what it earns
+    pub fn review_application(
+        ctx: Context<ReviewApplication>,
+        approve: bool,
+        tenant_yield_share_bps: Option<u16>,
+    ) -> Result<()> {
+        let application = &mut ctx.accounts.application;
+
+        require!(
+            application.status == ApplicationStatus::Pending,
+            RentalError::ApplicationNotPending
+        );
+        if let Some(share_bps) = tenant_yield_share_bps {
+            require!(approve && share_bps <= 10_000, RentalError::InvalidYieldShare);
+        }
+
+        application.status = if approve {
+            ApplicationStatus::Approved
+        } else {
+            ApplicationStatus::Rejected
+        };
+        application.tenant_yield_share_bps = tenant_yield_share_bps;
+
+        if approve {
+            application.approval_expires_at =
+                Clock::get()?.unix_timestamp + ctx.accounts.config.approval_expiry;
+
+            // Leases only follow approvals, so the landlord's reputation is opened by the first one
+            open_reputation(
+                ctx.accounts.landlord_reputation.to_account_info(),
+                ctx.accounts.landlord.to_account_info(),
+                ctx.accounts.landlord.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.property_program.to_account_info(),
+                ReputationRole::Landlord,
+            )?;
+        }
+
+        emit!(ApplicationReviewed {
+            application: application.key(),
+            property: application.property,
+            tenant: application.tenant,
+            approved: approve,
+            tenant_yield_share_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank returning the deposit of an application that is rejected, used,
+    /// expired, or still pending when another lease is booked.
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn refund_application<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundApplication<'info>>,
+    ) -> Result<()> {
+        let application = &ctx.accounts.application;
+        require!(
+            application.is_refundable(&ctx.accounts.property, Clock::get()?.unix_timestamp),
+            RentalError::ApplicationStillOpen
+        );
+
+        let refund = close_application(
+            application,
+            &ctx.accounts.mint,
+            &ctx.accounts.application_vault,
+            &ctx.accounts.tenant_token_account,
+            ctx.accounts.tenant.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        emit!(ApplicationRefunded {
+            application: application.key(),
+            property: application.property,
+            tenant: application.tenant,
+            amount: refund,
+            status: application.status,
+        });
+
+        Ok(())
+    }
+
+    /// Tenant withdraws an application the landlord has not rejected, reclaiming the deposit.
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn withdraw_application<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawApplication<'info>>,
+    ) -> Result<()> {
+        let application = &mut ctx.accounts.application;
+
+        require!(
+            matches!(
+                application.status,
+                ApplicationStatus::Pending | ApplicationStatus::Approved
+            ),
+            RentalError::ApplicationNotOpen
+        );
+        application.status = ApplicationStatus::Withdrawn;
+
+        let refund = close_application(
+            application,
+            &ctx.accounts.mint,
+            &ctx.accounts.application_vault,
+            &ctx.accounts.tenant_token_account,
+            ctx.accounts.tenant.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        emit!(ApplicationRefunded {
+            application: application.key(),
+            property: application.property,
+            tenant: application.tenant,
+            amount: refund,
+            status: application.status,
         });
 
         Ok(())
     }
 
-    pub fn pay_rent(ctx: Context<PayRent>) -> Result<()> {
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn pay_rent<'info>(
+        ctx: Context<'_, '_, '_, 'info, PayRent<'info>>,
+        amount: u64,
+    ) -> Result<()> {
         let agreement = &mut ctx.accounts.rental_agreement;
         let clock = Clock::get()?;
+        let payer = ctx.accounts.payer.key();
 
         require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(
+            agreement.payment_mode == PaymentMode::Periodic,
+            RentalError::WrongPaymentMode
+        );
+        require!(agreement.is_tenant(&payer), RentalError::NotTenant);
+        require!(amount > 0, RentalError::InvalidRentAmount);
         require!(clock.unix_timestamp <= agreement.end_date, RentalError::LeaseExpired);
 
-        // Check if payment is due (30 days = 2592000 seconds)
-        if agreement.last_payment_date > 0 {
-            let time_since_last_payment = clock.unix_timestamp - agreement.last_payment_date;
-            require!(
-                time_since_last_payment >= 2592000,
-                RentalError::PaymentNotDue
-            );
-        }
+        // Rent is only owed for periods that start within the lease
+        require!(
+            agreement.next_due_date < agreement.end_date,
+            RentalError::AllPeriodsPaid
+        );
+
+        // Allow paying up to max_prepaid_periods ahead of the due date
+        let prepay_window =
+            agreement.billing_period.seconds() * agreement.max_prepaid_periods as i64;
+        require!(
+            agreement.next_due_date <= clock.unix_timestamp + prepay_window,
+            RentalError::PaymentNotDue
+        );
+
+        // Accrue any late fee the crank has not picked up yet
+        let was_defaulted = agreement.payment_status == PaymentStatus::Defaulted;
+        agreement.assess_lateness(clock.unix_timestamp);
+        let mut defaulted = !was_defaulted && agreement.payment_status == PaymentStatus::Defaulted;
+
+        // Any tenant may chip in, but never more than what is still outstanding
+        let contribution = amount.min(agreement.outstanding_rent());
+
+        // Transfer rent from the paying tenant to landlord, less the platform fee. Any
+        // transfer fee the mint charges is withheld from what the recipients receive.
+        transfer_rent(
+            agreement.key(),
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.payer_token_account.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.landlord_token_account.to_account_info(),
+            ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            &[],
+            contribution,
+            ctx.accounts.config.fee_bps,
+        )?;
+
+        let agreement_key = agreement.key();
+        let closed_on_time = record_rent_payment(
+            agreement,
+            agreement_key,
+            payer,
+            contribution,
+            clock.unix_timestamp,
+        );
+        // Closing one period can leave the tenant defaulted on the next
+        defaulted |= closed_on_time.is_some() && agreement.payment_status == PaymentStatus::Defaulted;
+
+        record_payment_reputation(
+            ctx.accounts.tenant_reputation.to_account_info(),
+            ctx.accounts.rental_authority.to_account_info(),
+            ctx.bumps.rental_authority,
+            ctx.accounts.property_program.to_account_info(),
+            closed_on_time,
+            defaulted,
+            agreement.payment_count,
+        )
+    }
+
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn fund_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, FundStream<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(
+            agreement.payment_mode == PaymentMode::Streaming,
+            RentalError::WrongPaymentMode
+        );
+        require!(amount > 0, RentalError::InvalidRentAmount);
+        // Funding after the end could only stream back out through withdraw_unstreamed
+        require!(
+            Clock::get()?.unix_timestamp < agreement.end_date,
+            RentalError::LeaseExpired
+        );
 
-        // Transfer USDC from tenant to landlord
         let transfer_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            TokenTransfer {
+            TransferChecked {
                 from: ctx.accounts.tenant_token_account.to_account_info(),
-                to: ctx.accounts.landlord_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.stream_vault.to_account_info(),
                 authority: ctx.accounts.tenant.to_account_info(),
             },
-        );
-        token::transfer(transfer_ctx, agreement.rent_amount)?;
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
 
-        agreement.payment_count += 1;
-        agreement.last_payment_date = clock.unix_timestamp;
-        agreement.last_payment_amount = agreement.rent_amount;
-        agreement.total_paid += agreement.rent_amount;
-        agreement.payment_status = 0; // Reset to current after payment
+        agreement.stream_open = true;
+        ctx.accounts.stream_vault.reload()?;
 
-        emit!(RentPaid {
+        emit!(StreamFunded {
             agreement: agreement.key(),
             tenant: agreement.tenant,
-            landlord: agreement.landlord,
-            amount: agreement.rent_amount,
-            payment_number: agreement.payment_count,
+            amount,
+            balance: ctx.accounts.stream_vault.amount,
         });
 
         Ok(())
     }
 
-    pub fn end_lease(ctx: Context<EndLease>) -> Result<()> {
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn withdraw_streamed_rent<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawStreamedRent<'info>>,
+    ) -> Result<()> {
         let agreement = &mut ctx.accounts.rental_agreement;
+        let clock = Clock::get()?;
 
-        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(
+            agreement.payment_mode == PaymentMode::Streaming,
+            RentalError::WrongPaymentMode
+        );
 
-        agreement.is_active = false;
+        // Everything streamed so far that the landlord has not taken, as far as the balance covers
+        let streamed = agreement.streamed_rent(clock.unix_timestamp);
+        let amount = streamed
+            .saturating_sub(agreement.total_paid)
+            .min(ctx.accounts.stream_vault.amount);
+        require!(amount > 0, RentalError::NothingStreamed);
 
-        emit!(LeaseEnded {
+        let agreement_seeds = &[
+            b"rental",
+            agreement.property.as_ref(),
+            agreement.tenant.as_ref(),
+            &[agreement.bump],
+        ];
+        let signer = &[&agreement_seeds[..]];
+
+        transfer_rent(
+            agreement.key(),
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.stream_vault.to_account_info(),
+            agreement.to_account_info(),
+            ctx.accounts.landlord_token_account.to_account_info(),
+            ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount,
+            ctx.accounts.config.fee_bps,
+        )?;
+
+        agreement.total_paid += amount;
+        agreement.last_payment_date = clock.unix_timestamp;
+        agreement.last_payment_amount = amount;
+
+        emit!(StreamWithdrawn {
             agreement: agreement.key(),
-            property: agreement.property,
             landlord: agreement.landlord,
-            tenant: agreement.tenant,
+            amount,
+            streamed,
+            total_paid: agreement.total_paid,
         });
 
         Ok(())
     }
 
-    pub fn dispute_lease(ctx: Context<DisputeLease>, reason: String) -> Result<()> {
-        let agreement = &ctx.accounts.rental_agreement;
+    /// Settles the stream once the lease has ended: the landlord gets whatever streamed and
+    /// was not yet withdrawn, the tenant gets the rest, and the vault is closed.
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn withdraw_unstreamed<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawUnstreamed<'info>>,
+    ) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let clock = Clock::get()?;
 
-        require!(agreement.is_active, RentalError::LeaseNotActive);
-        require!(reason.len() <= 500, RentalError::ReasonTooLong);
+        require!(agreement.stream_open, RentalError::StreamNotOpen);
+        require!(
+            clock.unix_timestamp >= agreement.end_date,
+            RentalError::LeaseNotEnded
+        );
 
-        emit!(LeaseDisputed {
+        let balance = ctx.accounts.stream_vault.amount;
+        let landlord_amount = agreement
+            .streamed_rent(clock.unix_timestamp)
+            .saturating_sub(agreement.total_paid)
+            .min(balance);
+        let refund = balance - landlord_amount;
+
+        let agreement_seeds = &[
+            b"rental",
+            agreement.property.as_ref(),
+            agreement.tenant.as_ref(),
+            &[agreement.bump],
+        ];
+        let signer = &[&agreement_seeds[..]];
+
+        if landlord_amount > 0 {
+            transfer_rent(
+                agreement.key(),
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                ctx.accounts.stream_vault.to_account_info(),
+                agreement.to_account_info(),
+                ctx.accounts.landlord_token_account.to_account_info(),
+                ctx.accounts.treasury_token_account.to_account_info(),
+                ctx.remaining_accounts,
+                signer,
+                landlord_amount,
+                ctx.accounts.config.fee_bps,
+            )?;
+        }
+
+        if refund > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stream_vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.tenant_token_account.to_account_info(),
+                    authority: agreement.to_account_info(),
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_ctx, refund, ctx.accounts.mint.decimals)?;
+        }
+
+        harvest_withheld_fees(
+            &ctx.accounts.mint,
+            &ctx.accounts.stream_vault,
+            &ctx.accounts.token_program,
+        )?;
+
+        let close_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.stream_vault.to_account_info(),
+                destination: ctx.accounts.tenant.to_account_info(),
+                authority: agreement.to_account_info(),
+            },
+            signer,
+        );
+        token_interface::close_account(close_ctx)?;
+
+        if landlord_amount > 0 {
+            agreement.total_paid += landlord_amount;
+            agreement.last_payment_date = clock.unix_timestamp;
+            agreement.last_payment_amount = landlord_amount;
+        }
+        agreement.stream_open = false;
+
+        emit!(StreamSettled {
             agreement: agreement.key(),
-            initiator: ctx.accounts.initiator.key(),
-            reason,
+            landlord_amount,
+            refund,
+            total_paid: agreement.total_paid,
         });
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct CreateLease<'info> {
-    #[account(
-        init,
-        payer = tenant,
-        space = 8 + RentalAgreement::INIT_SPACE,
-        seeds = [b"rental", property.key().as_ref(), tenant.key().as_ref()],
-        bump
-    )]
-    pub rental_agreement: Account<'info, RentalAgreement>,
+    pub fn enable_autopay(ctx: Context<EnableAutopay>, allowance: u64) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let tenant = ctx.accounts.tenant.key();
 
-    /// CHECK: Property account from property-registry program
-    #[account(mut)]
-    pub property: UncheckedAccount<'info>,
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(agreement.is_tenant(&tenant), RentalError::NotTenant);
+        require!(allowance > 0, RentalError::InvalidAllowance);
+        require!(agreement.autopay.is_none(), RentalError::AutopayAlreadyEnabled);
 
-    /// CHECK: Landlord public key
-    pub landlord: UncheckedAccount<'info>,
+        // Let the agreement PDA pull rent from the tenant's account, up to the allowance
+        let approve_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.tenant_token_account.to_account_info(),
+                delegate: agreement.to_account_info(),
+                authority: ctx.accounts.tenant.to_account_info(),
+            },
+        );
+        token_interface::approve(approve_ctx, allowance)?;
 
-    #[account(mut)]
-    pub tenant: Signer<'info>,
+        agreement.autopay = Some(Autopay {
+            payer: tenant,
+            token_account: ctx.accounts.tenant_token_account.key(),
+        });
 
-    /// CHECK: The Property Registry Program
-    pub property_program: UncheckedAccount<'info>,
+        emit!(AutopayEnabled {
+            agreement: agreement.key(),
+            payer: tenant,
+            token_account: ctx.accounts.tenant_token_account.key(),
+            allowance,
+        });
 
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct PayRent<'info> {
-    #[account(
-        mut,
-        has_one = tenant,
-        has_one = landlord,
-        seeds = [b"rental", rental_agreement.property.as_ref(), tenant.key().as_ref()],
-        bump = rental_agreement.bump
-    )]
-    pub rental_agreement: Account<'info, RentalAgreement>,
+    pub fn disable_autopay(ctx: Context<DisableAutopay>) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let autopay = agreement.autopay.ok_or(RentalError::AutopayNotEnabled)?;
 
-    /// CHECK: Landlord public key
-    pub landlord: UncheckedAccount<'info>,
+        require_keys_eq!(autopay.payer, ctx.accounts.tenant.key(), RentalError::Unauthorized);
 
-    #[account(mut)]
-    pub tenant: Signer<'info>,
+        let revoke_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.tenant_token_account.to_account_info(),
+                authority: ctx.accounts.tenant.to_account_info(),
+            },
+        );
+        token_interface::revoke(revoke_ctx)?;
 
-    #[account(mut)]
-    pub tenant_token_account: Account<'info, TokenAccount>,
+        agreement.autopay = None;
 
-    #[account(mut)]
-    pub landlord_token_account: Account<'info, TokenAccount>,
+        emit!(AutopayDisabled {
+            agreement: agreement.key(),
+            payer: autopay.payer,
+        });
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct EndLease<'info> {
-    #[account(
-        mut,
-        has_one = landlord,
-        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
-        bump = rental_agreement.bump
-    )]
-    pub rental_agreement: Account<'info, RentalAgreement>,
+    /// Permissionless crank that pulls the open period's rent once it is due. Closing the
+    /// period moves the due date forward, so a period can never be collected twice.
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn collect_rent<'info>(ctx: Context<'_, '_, '_, 'info, CollectRent<'info>>) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let clock = Clock::get()?;
 
-    pub landlord: Signer<'info>,
-}
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(
+            agreement.payment_mode == PaymentMode::Periodic,
+            RentalError::WrongPaymentMode
+        );
+        require!(clock.unix_timestamp <= agreement.end_date, RentalError::LeaseExpired);
+        require!(
+            agreement.next_due_date < agreement.end_date,
+            RentalError::AllPeriodsPaid
+        );
+        require!(
+            agreement.next_due_date <= clock.unix_timestamp,
+            RentalError::PaymentNotDue
+        );
 
-#[derive(Accounts)]
-pub struct DisputeLease<'info> {
-    #[account(
-        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
-        bump = rental_agreement.bump
-    )]
-    pub rental_agreement: Account<'info, RentalAgreement>,
+        let autopay = agreement.autopay.ok_or(RentalError::AutopayNotEnabled)?;
 
-    pub initiator: Signer<'info>,
-}
+        let was_defaulted = agreement.payment_status == PaymentStatus::Defaulted;
+        agreement.assess_lateness(clock.unix_timestamp);
+        let mut defaulted = !was_defaulted && agreement.payment_status == PaymentStatus::Defaulted;
+        let amount = agreement.outstanding_rent();
 
-#[account]
-#[derive(InitSpace)]
-pub struct RentalAgreement {
+        // Pull the rent as the tenant's delegate, signed by the agreement PDA
+        let agreement_seeds = &[
+            b"rental",
+            agreement.property.as_ref(),
+            agreement.tenant.as_ref(),
+            &[agreement.bump],
+        ];
+        let signer = &[&agreement_seeds[..]];
+
+        transfer_rent(
+            agreement.key(),
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.autopay_token_account.to_account_info(),
+            agreement.to_account_info(),
+            ctx.accounts.landlord_token_account.to_account_info(),
+            ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount,
+            ctx.accounts.config.fee_bps,
+        )?;
+
+        let agreement_key = agreement.key();
+        let closed_on_time = record_rent_payment(
+            agreement,
+            agreement_key,
+            autopay.payer,
+            amount,
+            clock.unix_timestamp,
+        );
+        defaulted |= closed_on_time.is_some() && agreement.payment_status == PaymentStatus::Defaulted;
+
+        record_payment_reputation(
+            ctx.accounts.tenant_reputation.to_account_info(),
+            ctx.accounts.rental_authority.to_account_info(),
+            ctx.bumps.rental_authority,
+            ctx.accounts.property_program.to_account_info(),
+            closed_on_time,
+            defaulted,
+            agreement.payment_count,
+        )
+    }
+
+    pub fn accept_guarantee(ctx: Context<AcceptGuarantee>, cap: u64) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(cap > 0, RentalError::InvalidGuaranteeCap);
+        require!(
+            agreement.guarantee.is_none(),
+            RentalError::GuaranteeAlreadyAccepted
+        );
+
+        // Let the agreement PDA pull up to the cap from the guarantor's account
+        let approve_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.guarantor_token_account.to_account_info(),
+                delegate: agreement.to_account_info(),
+                authority: ctx.accounts.guarantor.to_account_info(),
+            },
+        );
+        token_interface::approve(approve_ctx, cap)?;
+
+        agreement.guarantee = Some(Guarantee {
+            token_account: ctx.accounts.guarantor_token_account.key(),
+            cap,
+            drawn: 0,
+        });
+
+        emit!(GuaranteeAccepted {
+            agreement: agreement.key(),
+            guarantor: ctx.accounts.guarantor.key(),
+            token_account: ctx.accounts.guarantor_token_account.key(),
+            cap,
+        });
+
+        Ok(())
+    }
+
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn draw_from_guarantor<'info>(
+        ctx: Context<'_, '_, '_, 'info, DrawFromGuarantor<'info>>,
+    ) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let clock = Clock::get()?;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(
+            agreement.next_due_date < agreement.end_date,
+            RentalError::AllPeriodsPaid
+        );
+
+        // The guarantor only steps in once the tenant has defaulted
+        let was_defaulted = agreement.payment_status == PaymentStatus::Defaulted;
+        agreement.assess_lateness(clock.unix_timestamp);
+        require!(
+            agreement.payment_status == PaymentStatus::Defaulted,
+            RentalError::TenantNotDefaulted
+        );
+        let mut defaulted = !was_defaulted;
+
+        let mut guarantee = agreement.guarantee.ok_or(RentalError::NoGuarantee)?;
+        let amount = agreement
+            .outstanding_rent()
+            .min(guarantee.cap - guarantee.drawn);
+        require!(amount > 0, RentalError::GuaranteeExhausted);
+
+        // Pull the overdue rent as the guarantor's delegate, signed by the agreement PDA
+        let agreement_seeds = &[
+            b"rental",
+            agreement.property.as_ref(),
+            agreement.tenant.as_ref(),
+            &[agreement.bump],
+        ];
+        let signer = &[&agreement_seeds[..]];
+
+        transfer_rent(
+            agreement.key(),
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.guarantor_token_account.to_account_info(),
+            agreement.to_account_info(),
+            ctx.accounts.landlord_token_account.to_account_info(),
+            ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            signer,
+            amount,
+            ctx.accounts.config.fee_bps,
+        )?;
+
+        guarantee.drawn += amount;
+        agreement.guarantee = Some(guarantee);
+
+        emit!(GuarantorDrawn {
+            agreement: agreement.key(),
+            guarantor: ctx.accounts.guarantor.key(),
+            amount,
+            drawn: guarantee.drawn,
+            cap: guarantee.cap,
+        });
+
+        let agreement_key = agreement.key();
+        let guarantor = ctx.accounts.guarantor.key();
+        let closed_on_time =
+            record_rent_payment(agreement, agreement_key, guarantor, amount, clock.unix_timestamp);
+        defaulted |= closed_on_time.is_some() && agreement.payment_status == PaymentStatus::Defaulted;
+
+        record_payment_reputation(
+            ctx.accounts.tenant_reputation.to_account_info(),
+            ctx.accounts.rental_authority.to_account_info(),
+            ctx.bumps.rental_authority,
+            ctx.accounts.property_program.to_account_info(),
+            closed_on_time,
+            defaulted,
+            agreement.payment_count,
+        )
+    }
+
+    pub fn assess_lateness(ctx: Context<AssessLateness>) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let clock = Clock::get()?;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+
+        let previous_status = agreement.payment_status;
+        let late_fee = agreement.assess_lateness(clock.unix_timestamp);
+        require!(
+            agreement.payment_status != previous_status,
+            RentalError::PaymentStatusUnchanged
+        );
+
+        emit!(PaymentStatusChanged {
+            agreement: agreement.key(),
+            tenant: agreement.tenant,
+            status: agreement.payment_status,
+            late_fee,
+            accrued_late_fee: agreement.accrued_late_fee,
+        });
+
+        if agreement.payment_status == PaymentStatus::Defaulted {
+            record_reputation(
+                ctx.accounts.tenant_reputation.to_account_info(),
+                ctx.accounts.rental_authority.to_account_info(),
+                ctx.bumps.rental_authority,
+                ctx.accounts.property_program.to_account_info(),
+                ReputationEvent::Default,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn propose_renewal(
+        ctx: Context<ProposeRenewal>,
+        rent_amount: u64,
+        lease_duration: i64,
+    ) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let config = &ctx.accounts.config;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(rent_amount > 0, RentalError::InvalidRentAmount);
+        require!(lease_duration > 0, RentalError::InvalidLeaseDuration);
+
+        // Capped against the latest agreed rent, even if it has not taken effect yet
+        let current_rent = agreement
+            .scheduled_rent
+            .map_or(agreement.rent_amount, |s| s.rent_amount);
+        let max_rent = current_rent as u128
+            * (10_000 + config.max_rent_increase_bps as u128)
+            / 10_000;
+        require!(
+            rent_amount as u128 <= max_rent,
+            RentalError::RentIncreaseTooHigh
+        );
+
+        agreement.pending_renewal = Some(RenewalTerms {
+            rent_amount,
+            lease_duration,
+        });
+
+        emit!(RenewalProposed {
+            agreement: agreement.key(),
+            landlord: agreement.landlord,
+            rent_amount,
+            lease_duration,
+        });
+
+        Ok(())
+    }
+
+    pub fn accept_renewal(ctx: Context<AcceptRenewal>) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        let terms = agreement
+            .pending_renewal
+            .take()
+            .ok_or(RentalError::NoPendingRenewal)?;
+
+        // Only one rent change can wait for its start date at a time
+        let now = Clock::get()?.unix_timestamp;
+        agreement.apply_scheduled_rent(now);
+        require!(
+            agreement.scheduled_rent.is_none(),
+            RentalError::RenewalAlreadyScheduled
+        );
+
+        let previous_rent = agreement.rent_amount;
+        let previous_end_date = agreement.end_date;
+
+        // Payment history and the billing anchor carry over unchanged
+        agreement.renew(terms, now);
+
+        // Push back the deposit refund date
+        update_escrow_lease_end(
+            agreement,
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.escrow_program.to_account_info(),
+        )?;
+
+        // Hold the extra time on the calendar, which fails if the unit is pre-let by then
+        let authority_seeds = &[RENTAL_AUTHORITY_SEED, &[ctx.bumps.rental_authority]];
+        let signer = &[&authority_seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.property_program.to_account_info(),
+            property_registry::cpi::accounts::ExtendBooking {
+                property: ctx.accounts.property.to_account_info(),
+                rental_authority: ctx.accounts.rental_authority.to_account_info(),
+            },
+            signer,
+        );
+        property_registry::cpi::extend_booking(cpi_ctx, agreement.key(), agreement.end_date)?;
+
+        emit!(LeaseRenewed {
+            agreement: agreement.key(),
+            tenant: agreement.tenant,
+            landlord: agreement.landlord,
+            previous_rent,
+            rent_amount: terms.rent_amount,
+            previous_end_date,
+            end_date: agreement.end_date,
+        });
+
+        Ok(())
+    }
+
+    pub fn end_lease(ctx: Context<EndLease>) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let clock = Clock::get()?;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        // Ending before the term is up requires notice
+        require!(
+            clock.unix_timestamp >= agreement.end_date,
+            RentalError::LeaseNotEnded
+        );
+
+        agreement.is_active = false;
+
+        // A rental only counts as successful if it ran its full term in good standing
+        let completed = agreement.payment_status == PaymentStatus::Current;
+
+        vacate_property(
+            agreement.key(),
+            ctx.accounts.property.to_account_info(),
+            ctx.accounts.rental_authority.to_account_info(),
+            ctx.bumps.rental_authority,
+            ctx.accounts.property_program.to_account_info(),
+            completed,
+        )?;
+
+        // The landlord saw the lease through; the tenant only if in good standing
+        let mut completions = vec![ctx.accounts.landlord_reputation.to_account_info()];
+        if completed {
+            completions.push(ctx.accounts.tenant_reputation.to_account_info());
+        }
+        for reputation in completions {
+            record_reputation(
+                reputation,
+                ctx.accounts.rental_authority.to_account_info(),
+                ctx.bumps.rental_authority,
+                ctx.accounts.property_program.to_account_info(),
+                ReputationEvent::LeaseCompleted,
+            )?;
+        }
+
+        emit!(LeaseEnded {
+            agreement: agreement.key(),
+            property: agreement.property,
+            landlord: agreement.landlord,
+            tenant: agreement.tenant,
+        });
+
+        Ok(())
+    }
+
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn give_notice<'info>(ctx: Context<'_, '_, '_, 'info, GiveNotice<'info>>) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let party = ctx.accounts.party.key();
+        let clock = Clock::get()?;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        require!(
+            agreement.termination_notice.is_none(),
+            RentalError::NoticeAlreadyGiven
+        );
+
+        require!(
+            agreement.counterparty(&party).is_some(),
+            RentalError::NotLeaseParty
+        );
+
+        // The party breaking the lease pays the break fee up front
+        let break_fee = agreement.termination_terms.break_fee;
+        if break_fee > 0 {
+            let (Some(party_token_account), Some(counterparty_token_account)) = (
+                &ctx.accounts.party_token_account,
+                &ctx.accounts.counterparty_token_account,
+            ) else {
+                return err!(RentalError::BreakFeeAccountsRequired);
+            };
+
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: party_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: counterparty_token_account.to_account_info(),
+                    authority: ctx.accounts.party.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_ctx, break_fee, ctx.accounts.mint.decimals)?;
+        }
+
+        let effective_date = clock.unix_timestamp + agreement.termination_terms.notice_period;
+        agreement.termination_notice = Some(TerminationNotice {
+            given_by: party,
+            effective_date,
+        });
+
+        emit!(NoticeGiven {
+            agreement: agreement.key(),
+            given_by: party,
+            effective_date,
+            break_fee,
+        });
+
+        Ok(())
+    }
+
+    pub fn finalize_termination(ctx: Context<FinalizeTermination>) -> Result<()> {
+        let agreement = &mut ctx.accounts.rental_agreement;
+        let clock = Clock::get()?;
+
+        require!(agreement.is_active, RentalError::LeaseNotActive);
+        let notice = agreement
+            .termination_notice
+            .ok_or(RentalError::NoNoticeGiven)?;
+        require!(
+            clock.unix_timestamp >= notice.effective_date,
+            RentalError::NoticePeriodNotOver
+        );
+
+        agreement.is_active = false;
+
+        vacate_property(
+            agreement.key(),
+            ctx.accounts.property.to_account_info(),
+            ctx.accounts.rental_authority.to_account_info(),
+            ctx.bumps.rental_authority,
+            ctx.accounts.property_program.to_account_info(),
+            false,
+        )?;
+
+        // Start the deposit inspection window from the termination date
+        if notice.effective_date < agreement.end_date {
+            agreement.end_date = notice.effective_date;
+            update_escrow_lease_end(
+                agreement,
+                ctx.accounts.escrow.to_account_info(),
+                ctx.accounts.escrow_program.to_account_info(),
+            )?;
+        }
+
+        emit!(LeaseTerminated {
+            agreement: agreement.key(),
+            property: agreement.property,
+            given_by: notice.given_by,
+            end_date: agreement.end_date,
+        });
+
+        Ok(())
+    }
+
+    /// Remaining accounts: transfer hook accounts, see `transfer_checked_with_hook`
+    pub fn close_lease<'info>(ctx: Context<'_, '_, '_, 'info, CloseLease<'info>>) -> Result<()> {
+        let agreement = &ctx.accounts.rental_agreement;
+
+        require!(!agreement.is_active, RentalError::LeaseStillActive);
+        require!(!agreement.stream_open, RentalError::StreamNotSettled);
+        require!(
+            ctx.accounts.dispute.data_is_empty(),
+            RentalError::DisputeNotClosed
+        );
+
+        // Close the escrow and its vault, signed by the agreement PDA
+        let agreement_seeds = &[
+            b"rental",
+            agreement.property.as_ref(),
+            agreement.tenant.as_ref(),
+            &[agreement.bump],
+        ];
+        let signer = &[&agreement_seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.escrow_program.to_account_info(),
+            escrow::cpi::accounts::CloseEscrow {
+                escrow: ctx.accounts.escrow.to_account_info(),
+                rental_agreement: agreement.to_account_info(),
+                tenant: ctx.accounts.tenant.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                tenant_token_account: ctx.accounts.tenant_token_account.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        escrow::cpi::close_escrow(cpi_ctx)?;
+
+        emit!(LeaseClosed {
+            agreement: agreement.key(),
+            property: agreement.property,
+            landlord: agreement.landlord,
+            tenant: agreement.tenant,
+            start_date: agreement.start_date,
+            end_date: agreement.end_date,
+            rent_amount: agreement.rent_amount,
+            deposit_amount: agreement.deposit_amount,
+            periods_paid: agreement.periods_paid,
+            total_paid: agreement.total_paid,
+            payment_status: agreement.payment_status,
+        });
+
+        Ok(())
+    }
+
+    pub fn close_dispute(ctx: Context<CloseDispute>) -> Result<()> {
+        let dispute = &ctx.accounts.dispute;
+
+        require!(
+            dispute.status == DisputeStatus::Resolved,
+            RentalError::DisputeNotResolved
+        );
+
+        emit!(DisputeClosed {
+            dispute: dispute.key(),
+            agreement: dispute.rental_agreement,
+            initiator: dispute.initiator,
+            tenant_evidence: dispute.tenant_evidence,
+            landlord_evidence: dispute.landlord_evidence,
+            landlord_amount: dispute.landlord_amount,
+            opened_at: dispute.opened_at,
+            resolved_at: dispute.resolved_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        arbitrator: Pubkey,
+        deduction_response_window: i64,
+        inspection_window: i64,
+        max_rent_increase_bps: u16,
+        fee_bps: u16,
+        treasury: Pubkey,
+        yield_strategy: Pubkey,
+        approval_expiry: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        require!(deduction_response_window > 0, RentalError::InvalidWindow);
+        require!(inspection_window > 0, RentalError::InvalidWindow);
+        require!(approval_expiry > 0, RentalError::InvalidWindow);
+        require!(fee_bps <= MAX_FEE_BPS, RentalError::FeeTooHigh);
+
+        config.admin = ctx.accounts.admin.key();
+        config.arbitrator = arbitrator;
+        config.deduction_response_window = deduction_response_window;
+        config.inspection_window = inspection_window;
+        config.max_rent_increase_bps = max_rent_increase_bps;
+        config.fee_bps = fee_bps;
+        config.treasury = treasury;
+        config.yield_strategy = yield_strategy;
+        config.approval_expiry = approval_expiry;
+        config.bump = ctx.bumps.config;
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            arbitrator: config.arbitrator,
+            deduction_response_window: config.deduction_response_window,
+            inspection_window: config.inspection_window,
+            max_rent_increase_bps: config.max_rent_increase_bps,
+            fee_bps: config.fee_bps,
+            treasury: config.treasury,
+            yield_strategy: config.yield_strategy,
+            approval_expiry: config.approval_expiry,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        admin: Option<Pubkey>,
+        arbitrator: Option<Pubkey>,
+        deduction_response_window: Option<i64>,
+        inspection_window: Option<i64>,
+        max_rent_increase_bps: Option<u16>,
+        fee_bps: Option<u16>,
+        treasury: Option<Pubkey>,
+        yield_strategy: Option<Pubkey>,
+        approval_expiry: Option<i64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        if let Some(new_admin) = admin {
+            config.admin = new_admin;
+        }
+
+        if let Some(new_arbitrator) = arbitrator {
+            config.arbitrator = new_arbitrator;
+        }
+
+        if let Some(window) = deduction_response_window {
+            require!(window > 0, RentalError::InvalidWindow);
+            config.deduction_response_window = window;
+        }
+
+        if let Some(window) = inspection_window {
+            require!(window > 0, RentalError::InvalidWindow);
+            config.inspection_window = window;
+        }
+
+        if let Some(cap) = max_rent_increase_bps {
+            config.max_rent_increase_bps = cap;
+        }
+
+        if let Some(fee) = fee_bps {
+            require!(fee <= MAX_FEE_BPS, RentalError::FeeTooHigh);
+            config.fee_bps = fee;
+        }
+
+        if let Some(new_treasury) = treasury {
+            config.treasury = new_treasury;
+        }
+
+        if let Some(strategy) = yield_strategy {
+            config.yield_strategy = strategy;
+        }
+
+        if let Some(expiry) = approval_expiry {
+            require!(expiry > 0, RentalError::InvalidWindow);
+            config.approval_expiry = expiry;
+        }
+
+        emit!(ConfigUpdated {
+            admin: config.admin,
+            arbitrator: config.arbitrator,
+            deduction_response_window: config.deduction_response_window,
+            inspection_window: config.inspection_window,
+            max_rent_increase_bps: config.max_rent_increase_bps,
+            fee_bps: config.fee_bps,
+            treasury: config.treasury,
+            yield_strategy: config.yield_strategy,
+            approval_expiry: config.approval_expiry,
+        });
+
+        Ok(())
+    }
+
+    pub fn dispute_lease(ctx: Context<DisputeLease>, reason: String) -> Result<()> {
+        let agreement = &ctx.accounts.rental_agreement;
+        let initiator = ctx.accounts.initiator.key();
+
+        require!(reason.len() <= 500, RentalError::ReasonTooLong);
+        require!(
+            initiator == agreement.tenant || initiator == agreement.landlord,
+            RentalError::NotLeaseParty
+        );
+
+        open_dispute(
+            agreement,
+            &mut ctx.accounts.dispute,
+            ctx.bumps.dispute,
+            initiator,
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.escrow_program.to_account_info(),
+        )?;
+
+        emit!(LeaseDisputed {
+            agreement: agreement.key(),
+            initiator,
+            reason,
+        });
+
+        Ok(())
+    }
+
+    pub fn reject_deduction(ctx: Context<RejectDeduction>, reason: String) -> Result<()> {
+        let agreement = &ctx.accounts.rental_agreement;
+        let escrow = &ctx.accounts.escrow;
+        let tenant = ctx.accounts.tenant.key();
+
+        require!(reason.len() <= 500, RentalError::ReasonTooLong);
+        require!(
+            escrow.has_pending_deduction(),
+            RentalError::NoPendingDeduction
+        );
+
+        let proposed_deduction = escrow.proposed_deduction;
+
+        // Rejecting a deduction hands the deposit to the arbitrator
+        open_dispute(
+            agreement,
+            &mut ctx.accounts.dispute,
+            ctx.bumps.dispute,
+            tenant,
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.escrow_program.to_account_info(),
+        )?;
+
+        emit!(DeductionRejected {
+            agreement: agreement.key(),
+            tenant,
+            proposed_deduction,
+            reason,
+        });
+
+        Ok(())
+    }
+
+    pub fn submit_evidence(ctx: Context<SubmitEvidence>, evidence_hash: [u8; 32]) -> Result<()> {
+        let agreement = &ctx.accounts.rental_agreement;
+        let dispute = &mut ctx.accounts.dispute;
+        let submitter = ctx.accounts.submitter.key();
+
+        require!(
+            dispute.status != DisputeStatus::Resolved,
+            RentalError::DisputeResolved
+        );
+
+        if submitter == agreement.tenant {
+            dispute.tenant_evidence = evidence_hash;
+        } else if submitter == agreement.landlord {
+            dispute.landlord_evidence = evidence_hash;
+        } else {
+            return err!(RentalError::NotLeaseParty);
+        }
+
+        dispute.status = DisputeStatus::EvidenceSubmitted;
+
+        emit!(EvidenceSubmitted {
+            dispute: dispute.key(),
+            submitter,
+            evidence_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Co-tenant token accounts, then any transfer hook accounts, follow as remaining accounts
+    /// for the escrow payout
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveDispute<'info>>,
+        landlord_amount: u64,
+    ) -> Result<()> {
+        let agreement = &ctx.accounts.rental_agreement;
+        let dispute = &mut ctx.accounts.dispute;
+
+        require!(
+            dispute.status != DisputeStatus::Resolved,
+            RentalError::DisputeResolved
+        );
+
+        // Split the deposit as ruled, signed by the agreement PDA
+        let agreement_seeds = &[
+            b"rental",
+            agreement.property.as_ref(),
+            agreement.tenant.as_ref(),
+            &[agreement.bump],
+        ];
+        let signer = &[&agreement_seeds[..]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.escrow_program.to_account_info(),
+            escrow::cpi::accounts::SettleDispute {
+                escrow: ctx.accounts.escrow.to_account_info(),
+                rental_agreement: agreement.to_account_info(),
+                landlord: ctx.accounts.landlord.to_account_info(),
+                tenant: ctx.accounts.tenant.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                escrow_token_account: ctx.accounts.escrow_token_account.to_account_info(),
+                landlord_token_account: ctx.accounts.landlord_token_account.to_account_info(),
+                tenant_token_account: ctx.accounts.tenant_token_account.to_account_info(),
+                landlord_reputation: ctx.accounts.landlord_reputation.to_account_info(),
+                tenant_reputation: ctx.accounts.tenant_reputation.to_account_info(),
+                escrow_authority: ctx.accounts.escrow_authority.to_account_info(),
+                property_program: ctx.accounts.property_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        escrow::cpi::settle_dispute(cpi_ctx, landlord_amount)?;
+
+        dispute.status = DisputeStatus::Resolved;
+        dispute.landlord_amount = landlord_amount;
+        dispute.resolved_at = Clock::get()?.unix_timestamp;
+
+        emit!(DisputeResolved {
+            dispute: dispute.key(),
+            agreement: agreement.key(),
+            arbitrator: ctx.accounts.arbitrator.key(),
+            landlord_amount,
+        });
+
+        Ok(())
+    }
+}
+
+/// Pays rent to the landlord, routing the platform fee to the treasury.
+fn transfer_rent<'info>(
+    agreement: Pubkey,
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    from: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    landlord_token_account: AccountInfo<'info>,
+    treasury_token_account: AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+    fee_bps: u16,
+) -> Result<()> {
+    let fee = (amount as u128 * fee_bps as u128 / 10_000) as u64;
+
+    for (to, value) in [(landlord_token_account, amount - fee), (treasury_token_account, fee)] {
+        if value == 0 {
+            continue;
+        }
+        let transfer_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.clone(),
+                mint: mint.to_account_info(),
+                to,
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        )
+        .with_remaining_accounts(hook_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, value, mint.decimals)?;
+    }
+
+    if fee > 0 {
+        emit!(PlatformFeeCollected {
+            agreement,
+            mint: mint.key(),
+            amount: fee,
+        });
+    }
+
+    Ok(())
+}
+
+/// Credits a rent payment to the open period, closing the period once it is paid in full.
+/// Returns whether a period closed on time, or None if it is still open.
+fn record_rent_payment(
+    agreement: &mut RentalAgreement,
+    key: Pubkey,
+    payer: Pubkey,
+    amount: u64,
+    now: i64,
+) -> Option<bool> {
+    let late_fee = agreement.accrued_late_fee;
+    let amount_due = agreement.rent_amount + late_fee;
+
+    agreement.period_collected += amount;
+    agreement.total_paid += amount;
+
+    emit!(RentContributed {
+        agreement: key,
+        payer,
+        amount,
+        collected: agreement.period_collected,
+        amount_due,
+    });
+
+    // The period stays open until the full amount has arrived
+    if agreement.period_collected < amount_due {
+        return None;
+    }
+
+    let on_time = agreement.payment_status == PaymentStatus::Current;
+    let period = agreement.periods_paid;
+    agreement.periods_paid += 1;
+    agreement.next_due_date = agreement.due_date_of(agreement.periods_paid);
+    agreement.payment_count += 1;
+    agreement.last_payment_date = now;
+    agreement.last_payment_amount = amount_due;
+    agreement.period_collected = 0;
+    agreement.accrued_late_fee = 0;
+    agreement.payment_status = PaymentStatus::Current; // Reset to current after payment
+
+    emit!(RentPaid {
+        agreement: key,
+        tenant: agreement.tenant,
+        landlord: agreement.landlord,
+        amount: amount_due,
+        late_fee,
+        payment_number: agreement.payment_count,
+        period,
+        next_due_date: agreement.next_due_date,
+    });
+
+    // A renewed rent starts with the first period of the renewed term
+    agreement.apply_scheduled_rent(now);
+
+    // A tenant several periods behind is still late for the next one
+    agreement.assess_lateness(now);
+
+    Some(on_time)
+}
+
+/// Returns an application's deposit to the tenant and closes its vault. Returns the refund.
+fn close_application<'info>(
+    application: &Account<'info, Application>,
+    mint: &InterfaceAccount<'info, Mint>,
+    application_vault: &InterfaceAccount<'info, TokenAccount>,
+    tenant_token_account: &InterfaceAccount<'info, TokenAccount>,
+    tenant: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    hook_accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    let application_seeds = &[
+        b"application",
+        application.property.as_ref(),
+        application.tenant.as_ref(),
+        &[application.bump],
+    ];
+    let signer = &[&application_seeds[..]];
+
+    let refund = application_vault.amount;
+    if refund > 0 {
+        let transfer_ctx = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: application_vault.to_account_info(),
+                mint: mint.to_account_info(),
+                to: tenant_token_account.to_account_info(),
+                authority: application.to_account_info(),
+            },
+            signer,
+        )
+        .with_remaining_accounts(hook_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, refund, mint.decimals)?;
+    }
+
+    harvest_withheld_fees(mint, application_vault, token_program)?;
+
+    let close_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: application_vault.to_account_info(),
+            destination: tenant,
+            authority: application.to_account_info(),
+        },
+        signer,
+    );
+    token_interface::close_account(close_ctx)?;
+
+    Ok(refund)
+}
+
+/// Opens a wallet's reputation in the registry unless it already exists.
+fn open_reputation<'info>(
+    reputation: AccountInfo<'info>,
+    wallet: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    property_program: AccountInfo<'info>,
+    role: ReputationRole,
+) -> Result<()> {
+    if !reputation.data_is_empty() {
+        return Ok(());
+    }
+
+    let cpi_ctx = CpiContext::new(
+        property_program,
+        property_registry::cpi::accounts::InitReputation {
+            reputation,
+            wallet,
+            payer,
+            system_program,
+        },
+    );
+    property_registry::cpi::init_reputation(cpi_ctx, role)
+}
+
+/// Updates a wallet's reputation in the registry, signed by this program's registry authority.
+fn record_reputation<'info>(
+    reputation: AccountInfo<'info>,
+    rental_authority: AccountInfo<'info>,
+    authority_bump: u8,
+    property_program: AccountInfo<'info>,
+    event: ReputationEvent,
+) -> Result<()> {
+    let authority_seeds = &[RENTAL_AUTHORITY_SEED, &[authority_bump]];
+    let signer = &[&authority_seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        property_program,
+        property_registry::cpi::accounts::RecordReputation {
+            reputation,
+            authority: rental_authority,
+        },
+        signer,
+    );
+    property_registry::cpi::record_reputation(cpi_ctx, event)
+}
+
+/// Records a closed rent period as on time or late, and any default the tenant fell into.
+fn record_payment_reputation<'info>(
+    tenant_reputation: AccountInfo<'info>,
+    rental_authority: AccountInfo<'info>,
+    authority_bump: u8,
+    property_program: AccountInfo<'info>,
+    closed_on_time: Option<bool>,
+    defaulted: bool,
+    payment_count: u32,
+) -> Result<()> {
+    for event in payment_reputation_events(closed_on_time, defaulted, payment_count) {
+        record_reputation(
+            tenant_reputation.clone(),
+            rental_authority.clone(),
+            authority_bump,
+            property_program.clone(),
+            event,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Reputation events for a rent payment: the period it closed, if any, then a new default.
+/// On-time credit stops after `MAX_CREDITED_PAYMENTS` periods so short billing periods
+/// can't be prepaid to farm a score.
+fn payment_reputation_events(
+    closed_on_time: Option<bool>,
+    defaulted: bool,
+    payment_count: u32,
+) -> Vec<ReputationEvent> {
+    let payment = closed_on_time.and_then(|on_time| {
+        if !on_time {
+            Some(ReputationEvent::LatePayment)
+        } else if payment_count <= MAX_CREDITED_PAYMENTS {
+            Some(ReputationEvent::OnTimePayment)
+        } else {
+            None
+        }
+    });
+    payment
+        .into_iter()
+        .chain(defaulted.then_some(ReputationEvent::Default))
+        .collect()
+}
+
+/// Returns the property to the registry, signed by this program's registry authority.
+fn vacate_property<'info>(
+    agreement: Pubkey,
+    property: AccountInfo<'info>,
+    rental_authority: AccountInfo<'info>,
+    authority_bump: u8,
+    property_program: AccountInfo<'info>,
+    completed: bool,
+) -> Result<()> {
+    let authority_seeds = &[RENTAL_AUTHORITY_SEED, &[authority_bump]];
+    let signer = &[&authority_seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        property_program,
+        property_registry::cpi::accounts::MarkVacated {
+            property,
+            rental_authority,
+        },
+        signer,
+    );
+    property_registry::cpi::mark_vacated(cpi_ctx, agreement, completed)
+}
+
+/// Copies the agreement's end date to its escrow, signed by the agreement PDA.
+fn update_escrow_lease_end<'info>(
+    agreement: &Account<'info, RentalAgreement>,
+    escrow: AccountInfo<'info>,
+    escrow_program: AccountInfo<'info>,
+) -> Result<()> {
+    let agreement_seeds = &[
+        b"rental",
+        agreement.property.as_ref(),
+        agreement.tenant.as_ref(),
+        &[agreement.bump],
+    ];
+    let signer = &[&agreement_seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        escrow_program,
+        escrow::cpi::accounts::UpdateLeaseEnd {
+            escrow,
+            rental_agreement: agreement.to_account_info(),
+        },
+        signer,
+    );
+    escrow::cpi::update_lease_end(cpi_ctx, agreement.end_date)
+}
+
+/// Records a new dispute and freezes the lease's escrow until the arbitrator rules.
+fn open_dispute<'info>(
+    agreement: &Account<'info, RentalAgreement>,
+    dispute: &mut Account<'info, Dispute>,
+    dispute_bump: u8,
+    initiator: Pubkey,
+    escrow: AccountInfo<'info>,
+    escrow_program: AccountInfo<'info>,
+) -> Result<()> {
+    dispute.rental_agreement = agreement.key();
+    dispute.initiator = initiator;
+    dispute.status = DisputeStatus::Open;
+    dispute.tenant_evidence = [0; 32];
+    dispute.landlord_evidence = [0; 32];
+    dispute.landlord_amount = 0;
+    dispute.opened_at = Clock::get()?.unix_timestamp;
+    dispute.resolved_at = 0;
+    dispute.bump = dispute_bump;
+
+    // Freeze the deposit, signed by the agreement PDA
+    let agreement_seeds = &[
+        b"rental",
+        agreement.property.as_ref(),
+        agreement.tenant.as_ref(),
+        &[agreement.bump],
+    ];
+    let signer = &[&agreement_seeds[..]];
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        escrow_program,
+        escrow::cpi::accounts::FreezeEscrow {
+            escrow,
+            rental_agreement: agreement.to_account_info(),
+        },
+        signer,
+    );
+    escrow::cpi::freeze_escrow(cpi_ctx)
+}
+
+#[derive(Accounts)]
+pub struct CreateLease<'info> {
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + RentalAgreement::INIT_SPACE,
+        seeds = [b"rental", property.key().as_ref(), tenant.key().as_ref()],
+        bump
+    )]
+    pub rental_agreement: Box<Account<'info, RentalAgreement>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        has_one = landlord @ RentalError::InvalidLandlord
+    )]
+    pub property: Box<Account<'info, Property>>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    /// CHECK: Tenant's reputation, opened by the registry on their first lease
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Tenant.seed(), tenant.key().as_ref()],
+        bump,
+        seeds::program = property_program.key()
+    )]
+    pub tenant_reputation: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"application", property.key().as_ref(), tenant.key().as_ref()],
+        bump = application.bump
+    )]
+    pub application: Box<Account<'info, Application>>,
+
+    /// CHECK: PDA that signs property status updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+
+    /// CHECK: Escrow PDA, initialized by the escrow program
+    #[account(
+        mut,
+        seeds = [b"escrow", rental_agreement.key().as_ref()],
+        bump,
+        seeds::program = escrow_program.key()
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    #[account(address = property.payment_mint @ RentalError::InvalidMint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = tenant_token_account.mint == property.payment_mint @ RentalError::InvalidMint
+    )]
+    pub tenant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Escrow vault, created and validated by the escrow program
+    #[account(mut)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitApplication<'info> {
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + Application::INIT_SPACE,
+        seeds = [b"application", property.key().as_ref(), tenant.key().as_ref()],
+        bump
+    )]
+    pub application: Box<Account<'info, Application>>,
+
+    pub property: Box<Account<'info, Property>>,
+
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    #[account(address = property.payment_mint @ RentalError::InvalidMint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == property.payment_mint @ RentalError::InvalidMint
+    )]
+    pub tenant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holds the application deposit, owned by the application PDA
+    #[account(
+        init,
+        payer = tenant,
+        associated_token::mint = mint,
+        associated_token::authority = application,
+        associated_token::token_program = token_program
+    )]
+    pub application_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReviewApplication<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"application", application.property.as_ref(), application.tenant.as_ref()],
+        bump = application.bump
+    )]
+    pub application: Account<'info, Application>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub landlord: Signer<'info>,
+
+    /// CHECK: Landlord's reputation, opened by the registry on their first approval
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Landlord.seed(), landlord.key().as_ref()],
+        bump,
+        seeds::program = property_program.key()
+    )]
+    pub landlord_reputation: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundApplication<'info> {
+    #[account(
+        mut,
+        close = tenant,
+        has_one = property,
+        has_one = tenant,
+        seeds = [b"application", application.property.as_ref(), application.tenant.as_ref()],
+        bump = application.bump
+    )]
+    pub application: Box<Account<'info, Application>>,
+
+    pub property: Box<Account<'info, Property>>,
+
+    /// CHECK: Applicant, receives the deposit and the rent of the closed accounts
+    #[account(mut)]
+    pub tenant: UncheckedAccount<'info>,
+
+    /// Writable for `harvest_withheld_fees`
+    #[account(mut, address = application.mint @ RentalError::InvalidMint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = application,
+        associated_token::token_program = token_program
+    )]
+    pub application_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == application.mint @ RentalError::InvalidMint
+    )]
+    pub tenant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawApplication<'info> {
+    #[account(
+        mut,
+        close = tenant,
+        has_one = tenant,
+        seeds = [b"application", application.property.as_ref(), application.tenant.as_ref()],
+        bump = application.bump
+    )]
+    pub application: Box<Account<'info, Application>>,
+
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    /// Writable for `harvest_withheld_fees`
+    #[account(mut, address = application.mint @ RentalError::InvalidMint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = application,
+        associated_token::token_program = token_program
+    )]
+    pub application_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == application.mint @ RentalError::InvalidMint
+    )]
+    pub tenant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct PayRent<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    /// Any tenant listed on the agreement
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(address = rental_agreement.payment_mint @ RentalError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = payer_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ RentalError::InvalidTreasury,
+        constraint = treasury_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Tenant.seed(), rental_agreement.tenant.as_ref()],
+        bump = tenant_reputation.bump,
+        seeds::program = property_program.key()
+    )]
+    pub tenant_reputation: Box<Account<'info, Reputation>>,
+
+    /// CHECK: PDA that signs reputation updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundStream<'info> {
+    #[account(
+        mut,
+        has_one = tenant,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub tenant: Signer<'info>,
+
+    #[account(address = rental_agreement.payment_mint @ RentalError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Agreement PDA's associated token account, created by the client before the first top-up
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = rental_agreement,
+        associated_token::token_program = token_program
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStreamedRent<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub landlord: Signer<'info>,
+
+    #[account(address = rental_agreement.payment_mint @ RentalError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = rental_agreement,
+        associated_token::token_program = token_program
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ RentalError::InvalidTreasury,
+        constraint = treasury_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnstreamed<'info> {
+    #[account(
+        mut,
+        has_one = tenant,
+        has_one = landlord,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Box<Account<'info, RentalAgreement>>,
+
+    /// Receives the unstreamed balance and the vault's rent
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    /// Writable for `harvest_withheld_fees`
+    #[account(mut, address = rental_agreement.payment_mint @ RentalError::InvalidMint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = rental_agreement,
+        associated_token::token_program = token_program
+    )]
+    pub stream_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub tenant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub landlord_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ RentalError::InvalidTreasury,
+        constraint = treasury_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct EnableAutopay<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub tenant: Signer<'info>,
+
+    #[account(
+        mut,
+        token::authority = tenant,
+        constraint = tenant_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct DisableAutopay<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub tenant: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = rental_agreement.autopay.map(|a| a.token_account) == Some(tenant_token_account.key()) @ RentalError::AutopayNotEnabled
+    )]
+    pub tenant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CollectRent<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    #[account(address = rental_agreement.payment_mint @ RentalError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = rental_agreement.autopay.map(|a| a.token_account) == Some(autopay_token_account.key()) @ RentalError::AutopayNotEnabled
+    )]
+    pub autopay_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ RentalError::InvalidTreasury,
+        constraint = treasury_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Tenant.seed(), rental_agreement.tenant.as_ref()],
+        bump = tenant_reputation.bump,
+        seeds::program = property_program.key()
+    )]
+    pub tenant_reputation: Box<Account<'info, Reputation>>,
+
+    /// CHECK: PDA that signs reputation updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptGuarantee<'info> {
+    #[account(
+        mut,
+        constraint = rental_agreement.guarantor == Some(guarantor.key()) @ RentalError::NotGuarantor,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub guarantor: Signer<'info>,
+
+    #[account(
+        mut,
+        token::authority = guarantor,
+        constraint = guarantor_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub guarantor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct DrawFromGuarantor<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        constraint = rental_agreement.guarantor == Some(guarantor.key()) @ RentalError::NotGuarantor,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    /// CHECK: Guarantor public key
+    pub guarantor: UncheckedAccount<'info>,
+
+    #[account(address = rental_agreement.payment_mint @ RentalError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = rental_agreement.guarantee.map(|g| g.token_account) == Some(guarantor_token_account.key()) @ RentalError::NoGuarantee
+    )]
+    pub guarantor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = landlord,
+        constraint = landlord_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub landlord_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == config.treasury @ RentalError::InvalidTreasury,
+        constraint = treasury_token_account.mint == rental_agreement.payment_mint @ RentalError::InvalidMint
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Tenant.seed(), rental_agreement.tenant.as_ref()],
+        bump = tenant_reputation.bump,
+        seeds::program = property_program.key()
+    )]
+    pub tenant_reputation: Box<Account<'info, Reputation>>,
+
+    /// CHECK: PDA that signs reputation updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AssessLateness<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Tenant.seed(), rental_agreement.tenant.as_ref()],
+        bump = tenant_reputation.bump,
+        seeds::program = property_program.key()
+    )]
+    pub tenant_reputation: Box<Account<'info, Reputation>>,
+
+    /// CHECK: PDA that signs reputation updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRenewal<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub landlord: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptRenewal<'info> {
+    #[account(
+        mut,
+        has_one = tenant,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub tenant: Signer<'info>,
+
+    /// CHECK: Escrow holding the deposit, validated by the escrow program
+    #[account(
+        mut,
+        address = rental_agreement.escrow
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+
+    #[account(
+        mut,
+        address = rental_agreement.property
+    )]
+    pub property: Box<Account<'info, Property>>,
+
+    /// CHECK: PDA that signs property calendar updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct EndLease<'info> {
+    #[account(
+        mut,
+        has_one = landlord,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        mut,
+        address = rental_agreement.property
+    )]
+    pub property: Account<'info, Property>,
+
+    pub landlord: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Tenant.seed(), rental_agreement.tenant.as_ref()],
+        bump = tenant_reputation.bump,
+        seeds::program = property_program.key()
+    )]
+    pub tenant_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation", ReputationRole::Landlord.seed(), rental_agreement.landlord.as_ref()],
+        bump = landlord_reputation.bump,
+        seeds::program = property_program.key()
+    )]
+    pub landlord_reputation: Box<Account<'info, Reputation>>,
+
+    /// CHECK: PDA that signs property status updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct GiveNotice<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    pub party: Signer<'info>,
+
+    #[account(address = rental_agreement.payment_mint @ RentalError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Only needed when the lease carries a break fee
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = party,
+    )]
+    pub party_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = rental_agreement.counterparty(&party.key()).unwrap_or_default(),
+    )]
+    pub counterparty_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeTermination<'info> {
+    #[account(
+        mut,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Box<Account<'info, RentalAgreement>>,
+
+    #[account(
+        mut,
+        address = rental_agreement.property
+    )]
+    pub property: Box<Account<'info, Property>>,
+
+    /// CHECK: PDA that signs property status updates in the registry
+    #[account(
+        seeds = [RENTAL_AUTHORITY_SEED],
+        bump
+    )]
+    pub rental_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+
+    /// CHECK: Escrow holding the deposit, validated by the escrow program
+    #[account(
+        mut,
+        address = rental_agreement.escrow
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLease<'info> {
+    #[account(
+        mut,
+        close = tenant,
+        has_one = tenant,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Box<Account<'info, RentalAgreement>>,
+
+    /// CHECK: Tenant public key, receives the rent of the closed accounts
+    #[account(mut)]
+    pub tenant: UncheckedAccount<'info>,
+
+    /// CHECK: Must already be closed so the next lease can open its own dispute
+    #[account(
+        seeds = [b"dispute", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub dispute: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow holding the deposit, validated by the escrow program
+    #[account(
+        mut,
+        address = rental_agreement.escrow
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    /// CHECK: Payment mint, validated by the escrow program
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow vault, validated by the escrow program
+    #[account(mut)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Tenant token account, validated by the escrow program
+    #[account(mut)]
+    pub tenant_token_account: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDispute<'info> {
+    #[account(
+        mut,
+        close = initiator,
+        has_one = initiator,
+        seeds = [b"dispute", dispute.rental_agreement.as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    /// CHECK: Party that opened the dispute, receives its rent
+    #[account(mut)]
+    pub initiator: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub program: Program<'info, crate::program::RentalAgreement>,
+
+    #[account(
+        constraint = is_upgrade_authority(&program, &program_data, admin.key)? @ RentalError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        has_one = admin @ RentalError::Unauthorized,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeLease<'info> {
+    #[account(
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + Dispute::INIT_SPACE,
+        seeds = [b"dispute", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    /// CHECK: Escrow holding the deposit, validated by the escrow program
+    #[account(
+        mut,
+        address = rental_agreement.escrow
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RejectDeduction<'info> {
+    #[account(
+        has_one = tenant,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Box<Account<'info, RentalAgreement>>,
+
+    #[account(
+        init,
+        payer = tenant,
+        space = 8 + Dispute::INIT_SPACE,
+        seeds = [b"dispute", rental_agreement.key().as_ref()],
+        bump
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    #[account(mut)]
+    pub tenant: Signer<'info>,
+
+    #[account(
+        mut,
+        address = rental_agreement.escrow
+    )]
+    pub escrow: Box<Account<'info, escrow::Escrow>>,
+
+    pub escrow_program: Program<'info, EscrowProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitEvidence<'info> {
+    #[account(
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Account<'info, RentalAgreement>,
+
+    #[account(
+        mut,
+        has_one = rental_agreement,
+        seeds = [b"dispute", rental_agreement.key().as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    pub submitter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = arbitrator @ RentalError::NotArbitrator
+    )]
+    pub config: Account<'info, Config>,
+
+    pub arbitrator: Signer<'info>,
+
+    #[account(
+        has_one = landlord,
+        has_one = tenant,
+        seeds = [b"rental", rental_agreement.property.as_ref(), rental_agreement.tenant.as_ref()],
+        bump = rental_agreement.bump
+    )]
+    pub rental_agreement: Box<Account<'info, RentalAgreement>>,
+
+    #[account(
+        mut,
+        has_one = rental_agreement,
+        seeds = [b"dispute", rental_agreement.key().as_ref()],
+        bump = dispute.bump
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    /// CHECK: Landlord public key
+    pub landlord: UncheckedAccount<'info>,
+
+    /// CHECK: Tenant public key
+    pub tenant: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow holding the deposit, validated by the escrow program
+    #[account(
+        mut,
+        address = rental_agreement.escrow
+    )]
+    pub escrow: UncheckedAccount<'info>,
+
+    /// CHECK: Payment mint, validated by the escrow program
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow vault, validated by the escrow program
+    #[account(mut)]
+    pub escrow_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Landlord token account, validated by the escrow program
+    #[account(mut)]
+    pub landlord_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Tenant token account, validated by the escrow program
+    #[account(mut)]
+    pub tenant_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Landlord reputation, validated by the escrow program
+    #[account(mut)]
+    pub landlord_reputation: UncheckedAccount<'info>,
+
+    /// CHECK: Tenant reputation, validated by the escrow program
+    #[account(mut)]
+    pub tenant_reputation: UncheckedAccount<'info>,
+
+    /// CHECK: Escrow's registry authority PDA, validated by the escrow program
+    pub escrow_authority: UncheckedAccount<'info>,
+
+    pub property_program: Program<'info, PropertyRegistry>,
+    pub escrow_program: Program<'info, EscrowProgram>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// property, landlord and tenant must stay the leading fields: escrow reads them directly
+#[account]
+#[derive(InitSpace)]
+pub struct RentalAgreement {
+    pub property: Pubkey,           // 32
+    pub landlord: Pubkey,           // 32
+    pub tenant: Pubkey,             // 32
+    pub start_date: i64,            // 8
+    pub end_date: i64,              // 8
+    pub rent_amount: u64,           // 8
+    pub deposit_amount: u64,        // 8
+    pub escrow: Pubkey,             // 32 - Escrow holding the deposit
+    pub payment_count: u32,         // 4
+    pub is_active: bool,            // 1
+    pub last_payment_date: i64,     // 8
+    pub last_payment_amount: u64,   // 8 - Track last payment
+    pub total_paid: u64,            // 8 - Total amount paid
+    pub payment_status: PaymentStatus, // 1
+    pub bump: u8,                   // 1
+    pub late_fee_policy: LateFeePolicy, // 26 - Copied from the property
+    pub accrued_late_fee: u64,      // 8 - Collected with the next rent payment
+    pub billing_anchor: i64,        // 8 - Due date of the first period
+    pub billing_period: BillingPeriod, // 9 - Copied from the property
+    pub max_prepaid_periods: u8,    // 1 - Copied from the property
+    pub periods_paid: u32,          // 4 - Periods settled so far
+    pub next_due_date: i64,         // 8 - Due date of the first unpaid period
+    pub pending_renewal: Option<RenewalTerms>, // 1 + 16 - Proposed by the landlord
+    pub scheduled_rent: Option<ScheduledRent>, // 1 + 16 - Accepted renewal rent, not yet in effect
+    pub termination_terms: TerminationTerms, // 16 - Copied from the property
+    pub termination_notice: Option<TerminationNotice>, // 1 + 40 - Set once a party gives notice
+    #[max_len(MAX_TENANTS)]
+    pub tenant_shares: Vec<TenantShare>, // 4 + 5 * 34 - Primary tenant first
+    pub guarantor: Option<Pubkey>,  // 1 + 32
+    pub period_collected: u64,      // 8 - Paid so far toward the open period
+    pub guarantee: Option<Guarantee>, // 1 + 48 - Set once the guarantor approves a cap
+    pub payment_mint: Pubkey,       // 32 - Copied from the property
+    pub autopay: Option<Autopay>,   // 1 + 64 - Set while a tenant has delegated rent collection
+    pub payment_mode: PaymentMode,  // 1 - Copied from the property
+    pub stream_accrued: u64,        // 8 - Rent streamed up to the checkpoint
+    pub stream_checkpoint: i64,     // 8 - Streaming continues from here at the current rent
+    pub stream_open: bool,          // 1 - Stream vault funded and not yet settled
+}
+
+impl RentalAgreement {
+    pub fn is_tenant(&self, key: &Pubkey) -> bool {
+        self.tenant_shares.iter().any(|s| s.tenant == *key)
+    }
+
+    /// The other side of the lease from `party`, or None if `party` is neither
+    pub fn counterparty(&self, party: &Pubkey) -> Option<Pubkey> {
+        if *party == self.tenant {
+            Some(self.landlord)
+        } else if *party == self.landlord {
+            Some(self.tenant)
+        } else {
+            None
+        }
+    }
+
+    /// Rent streamed to the landlord by `now`, accruing per second until the lease ends
+    pub fn streamed_rent(&self, now: i64) -> u64 {
+        let now = now.min(self.end_date);
+        let period = self.billing_period.seconds() as u128;
+        let accrue = |rent: u64, from: i64, to: i64| {
+            (rent as u128 * (to - from).max(0) as u128 / period) as u64
+        };
+
+        let accrued = match self.scheduled_rent {
+            Some(scheduled) => {
+                let switch = scheduled.effective_at.min(now);
+                accrue(self.rent_amount, self.stream_checkpoint, switch)
+                    + accrue(scheduled.rent_amount, switch.max(self.stream_checkpoint), now)
+            }
+            None => accrue(self.rent_amount, self.stream_checkpoint, now),
+        };
+        self.stream_accrued + accrued
+    }
+
+    /// Rent plus accrued late fee not yet collected for the open period
+    pub fn outstanding_rent(&self) -> u64 {
+        (self.rent_amount + self.accrued_late_fee).saturating_sub(self.period_collected)
+    }
+
+    /// Extends the lease by accepted renewal terms. The new rent applies from the
+    /// current end date, so the period already open keeps the rent it opened with.
+    pub fn renew(&mut self, terms: RenewalTerms, now: i64) {
+        self.scheduled_rent = Some(ScheduledRent {
+            rent_amount: terms.rent_amount,
+            effective_at: self.end_date,
+        });
+        self.end_date += terms.lease_duration;
+        self.apply_scheduled_rent(now);
+    }
+
+    /// Switches to the scheduled rent once it is due. Periodic leases switch between
+    /// periods only, at the first period starting on or after the effective date.
+    pub fn apply_scheduled_rent(&mut self, now: i64) {
+        let Some(scheduled) = self.scheduled_rent else {
+            return;
+        };
+
+        match self.payment_mode {
+            PaymentMode::Periodic => {
+                if self.period_collected > 0 || self.next_due_date < scheduled.effective_at {
+                    return;
+                }
+            }
+            PaymentMode::Streaming => {
+                if now < scheduled.effective_at {
+                    return;
+                }
+                // Lock in what has streamed so far before the rate changes
+                self.stream_accrued = self.streamed_rent(now);
+                self.stream_checkpoint = now.min(self.end_date);
+            }
+        }
+
+        self.rent_amount = scheduled.rent_amount;
+        self.scheduled_rent = None;
+    }
+
+    /// Due date of the given zero-based period, anchored so late payments never shift it
+    pub fn due_date_of(&self, period: u32) -> i64 {
+        self.billing_anchor + self.billing_period.seconds() * period as i64
+    }
+
+    /// Moves a lease to Late or Defaulted once its thresholds have passed,
+    /// accruing the late fee once per missed payment. Returns the fee accrued now.
+    pub fn assess_lateness(&mut self, now: i64) -> u64 {
+        // Streaming leases have no due dates to fall behind on
+        if self.payment_mode == PaymentMode::Streaming {
+            return 0;
+        }
+
+        let policy = self.late_fee_policy;
+        let overdue = now - self.next_due_date;
+        let mut late_fee = 0;
+
+        if self.payment_status == PaymentStatus::Current && overdue > policy.grace_period {
+            late_fee = policy.fee_for(self.rent_amount);
+            self.accrued_late_fee += late_fee;
+            self.payment_status = PaymentStatus::Late;
+        }
+
+        if self.payment_status == PaymentStatus::Late
+            && policy.default_after > 0
+            && overdue > policy.default_after
+        {
+            self.payment_status = PaymentStatus::Defaulted;
+        }
+
+        late_fee
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,              // 32 - May update the config
+    pub arbitrator: Pubkey,         // 32 - Resolves lease disputes
+    pub bump: u8,                   // 1
+    pub deduction_response_window: i64, // 8 - Seconds a tenant has to answer a deposit deduction
+    pub inspection_window: i64,     // 8 - Seconds after lease end before the deposit auto-refunds
+    pub max_rent_increase_bps: u16, // 2 - Largest rent increase allowed at renewal
+    pub fee_bps: u16,               // 2 - Platform's cut of every rent payment
+    pub treasury: Pubkey,           // 32 - Owner of the token accounts that receive platform fees
+    pub yield_strategy: Pubkey,     // 32 - Approved deposit yield strategy, default = none
+    pub approval_expiry: i64,       // 8 - Seconds an application approval stays valid
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Application {
     pub property: Pubkey,           // 32
-    pub landlord: Pubkey,           // 32
+    pub landlord: Pubkey,           // 32 - Reviews the application
     pub tenant: Pubkey,             // 32
-    pub start_date: i64,            // 8
-    pub end_date: i64,              // 8
-    pub rent_amount: u64,           // 8
-    pub deposit_amount: u64,        // 8
-    pub payment_count: u8,          // 1
-    pub is_active: bool,            // 1
-    pub last_payment_date: i64,     // 8
-    pub last_payment_amount: u64,   // 8 - Track last payment
-    pub total_paid: u64,            // 8 - Total amount paid
-    pub payment_status: u8,         // 1 - 0=current, 1=late, 2=defaulted
+    pub profile_hash: [u8; 32],     // 32 - Hash of the off-chain applicant profile
+    pub mint: Pubkey,               // 32 - Mint the application deposit was paid in
+    pub deposit_amount: u64,        // 8 - Refundable application deposit held in the vault
+    pub status: ApplicationStatus,  // 1
+    pub created_at: i64,            // 8
+    pub bump: u8,                   // 1
+    pub tenant_yield_share_bps: Option<u16>, // 1 + 2 - Deposit yield offered at approval, None = no yield
+    pub approval_expires_at: i64,   // 8 - Approval lapses unless leased by then
+}
+
+impl Application {
+    /// Whether anyone may close the application and refund its deposit
+    pub fn is_refundable(&self, property: &Property, now: i64) -> bool {
+        let settled = matches!(
+            self.status,
+            ApplicationStatus::Rejected | ApplicationStatus::Leased
+        );
+        // An approval the tenant never turned into a lease lapses
+        let expired = self.status == ApplicationStatus::Approved && now >= self.approval_expires_at;
+        // Open applications lapse once the property is deactivated. A pending one also lapses
+        // once another lease is booked; an approval stands until it expires, since the
+        // booking may be for other dates.
+        let overtaken = property.status == PropertyStatus::Deactivated
+            || (self.status == ApplicationStatus::Pending && self.created_at <= property.last_booked_at);
+        settled || expired || overtaken
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Dispute {
+    pub rental_agreement: Pubkey,   // 32
+    pub initiator: Pubkey,          // 32
+    pub status: DisputeStatus,      // 1
+    pub tenant_evidence: [u8; 32],  // 32 - Hash of the tenant's evidence bundle
+    pub landlord_evidence: [u8; 32], // 32 - Hash of the landlord's evidence bundle
+    pub landlord_amount: u64,       // 8 - Deposit awarded to the landlord
+    pub opened_at: i64,             // 8
+    pub resolved_at: i64,           // 8
     pub bump: u8,                   // 1
 }
 
+#[event]
+pub struct ApplicationSubmitted {
+    pub application: Pubkey,
+    pub property: Pubkey,
+    pub tenant: Pubkey,
+    pub profile_hash: [u8; 32],
+    pub deposit_amount: u64,
+}
+
+#[event]
+pub struct ApplicationReviewed {
+    pub application: Pubkey,
+    pub property: Pubkey,
+    pub tenant: Pubkey,
+    pub approved: bool,
+    pub tenant_yield_share_bps: Option<u16>,
+}
+
+#[event]
+pub struct ApplicationRefunded {
+    pub application: Pubkey,
+    pub property: Pubkey,
+    pub tenant: Pubkey,
+    pub amount: u64,
+    pub status: ApplicationStatus,
+}
+
 #[event]
 pub struct LeaseCreated {
     pub agreement: Pubkey,
@@ -302,6 +3307,80 @@ pub struct LeaseCreated {
     pub tenant: Pubkey,
     pub rent_amount: u64,
     pub deposit_amount: u64,
+    pub co_tenants: Vec<TenantShare>,
+    pub guarantor: Option<Pubkey>,
+}
+
+#[event]
+pub struct RentContributed {
+    pub agreement: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub collected: u64,
+    pub amount_due: u64,
+}
+
+#[event]
+pub struct PlatformFeeCollected {
+    pub agreement: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct StreamFunded {
+    pub agreement: Pubkey,
+    pub tenant: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct StreamWithdrawn {
+    pub agreement: Pubkey,
+    pub landlord: Pubkey,
+    pub amount: u64,
+    pub streamed: u64,
+    pub total_paid: u64,
+}
+
+#[event]
+pub struct StreamSettled {
+    pub agreement: Pubkey,
+    pub landlord_amount: u64,
+    pub refund: u64,
+    pub total_paid: u64,
+}
+
+#[event]
+pub struct AutopayEnabled {
+    pub agreement: Pubkey,
+    pub payer: Pubkey,
+    pub token_account: Pubkey,
+    pub allowance: u64,
+}
+
+#[event]
+pub struct AutopayDisabled {
+    pub agreement: Pubkey,
+    pub payer: Pubkey,
+}
+
+#[event]
+pub struct GuaranteeAccepted {
+    pub agreement: Pubkey,
+    pub guarantor: Pubkey,
+    pub token_account: Pubkey,
+    pub cap: u64,
+}
+
+#[event]
+pub struct GuarantorDrawn {
+    pub agreement: Pubkey,
+    pub guarantor: Pubkey,
+    pub amount: u64,
+    pub drawn: u64,
+    pub cap: u64,
 }
 
 #[event]
@@ -310,7 +3389,54 @@ pub struct RentPaid {
     pub tenant: Pubkey,
     pub landlord: Pubkey,
     pub amount: u64,
-    pub payment_number: u8,
+    pub late_fee: u64,
+    pub payment_number: u32,
+    pub period: u32,
+    pub next_due_date: i64,
+}
+
+#[event]
+pub struct PaymentStatusChanged {
+    pub agreement: Pubkey,
+    pub tenant: Pubkey,
+    pub status: PaymentStatus,
+    pub late_fee: u64,
+    pub accrued_late_fee: u64,
+}
+
+#[event]
+pub struct RenewalProposed {
+    pub agreement: Pubkey,
+    pub landlord: Pubkey,
+    pub rent_amount: u64,
+    pub lease_duration: i64,
+}
+
+#[event]
+pub struct LeaseRenewed {
+    pub agreement: Pubkey,
+    pub tenant: Pubkey,
+    pub landlord: Pubkey,
+    pub previous_rent: u64,
+    pub rent_amount: u64,       // Applies from previous_end_date
+    pub previous_end_date: i64,
+    pub end_date: i64,
+}
+
+#[event]
+pub struct NoticeGiven {
+    pub agreement: Pubkey,
+    pub given_by: Pubkey,
+    pub effective_date: i64,
+    pub break_fee: u64,
+}
+
+#[event]
+pub struct LeaseTerminated {
+    pub agreement: Pubkey,
+    pub property: Pubkey,
+    pub given_by: Pubkey,
+    pub end_date: i64,
 }
 
 #[event]
@@ -321,6 +3447,33 @@ pub struct LeaseEnded {
     pub tenant: Pubkey,
 }
 
+#[event]
+pub struct LeaseClosed {
+    pub agreement: Pubkey,
+    pub property: Pubkey,
+    pub landlord: Pubkey,
+    pub tenant: Pubkey,
+    pub start_date: i64,
+    pub end_date: i64,
+    pub rent_amount: u64,
+    pub deposit_amount: u64,
+    pub periods_paid: u32,
+    pub total_paid: u64,
+    pub payment_status: PaymentStatus,
+}
+
+#[event]
+pub struct DisputeClosed {
+    pub dispute: Pubkey,
+    pub agreement: Pubkey,
+    pub initiator: Pubkey,
+    pub tenant_evidence: [u8; 32],
+    pub landlord_evidence: [u8; 32],
+    pub landlord_amount: u64,
+    pub opened_at: i64,
+    pub resolved_at: i64,
+}
+
 #[event]
 pub struct LeaseDisputed {
     pub agreement: Pubkey,
@@ -328,6 +3481,42 @@ pub struct LeaseDisputed {
     pub reason: String,
 }
 
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub arbitrator: Pubkey,
+    pub deduction_response_window: i64,
+    pub inspection_window: i64,
+    pub max_rent_increase_bps: u16,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub yield_strategy: Pubkey,
+    pub approval_expiry: i64,
+}
+
+#[event]
+pub struct DeductionRejected {
+    pub agreement: Pubkey,
+    pub tenant: Pubkey,
+    pub proposed_deduction: u64,
+    pub reason: String,
+}
+
+#[event]
+pub struct EvidenceSubmitted {
+    pub dispute: Pubkey,
+    pub submitter: Pubkey,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub dispute: Pubkey,
+    pub agreement: Pubkey,
+    pub arbitrator: Pubkey,
+    pub landlord_amount: u64,
+}
+
 #[error_code]
 pub enum RentalError {
     #[msg("Rent amount must be greater than 0")]
@@ -344,4 +3533,512 @@ pub enum RentalError {
     PaymentNotDue,
     #[msg("Dispute reason is too long (max 500 characters)")]
     ReasonTooLong,
+    #[msg("Property is not available for rent")]
+    PropertyNotAvailable,
+    #[msg("Landlord does not match the property listing")]
+    InvalidLandlord,
+    #[msg("Payment status is already up to date")]
+    PaymentStatusUnchanged,
+    #[msg("Rent for every period of the lease has been paid")]
+    AllPeriodsPaid,
+    #[msg("Signer is not the tenant or landlord of this lease")]
+    NotLeaseParty,
+    #[msg("Signer is not the configured arbitrator")]
+    NotArbitrator,
+    #[msg("Dispute has already been resolved")]
+    DisputeResolved,
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+    #[msg("Window must be greater than 0")]
+    InvalidWindow,
+    #[msg("No deposit deduction is pending")]
+    NoPendingDeduction,
+    #[msg("Lease is still active")]
+    LeaseStillActive,
+    #[msg("Close the lease's dispute first")]
+    DisputeNotClosed,
+    #[msg("Dispute has not been resolved")]
+    DisputeNotResolved,
+    #[msg("Rent increase exceeds the configured cap")]
+    RentIncreaseTooHigh,
+    #[msg("No lease renewal is pending")]
+    NoPendingRenewal,
+    #[msg("Previous renewal has not taken effect yet")]
+    RenewalAlreadyScheduled,
+    #[msg("Lease term has not ended yet, give notice to end it early")]
+    LeaseNotEnded,
+    #[msg("Notice has already been given")]
+    NoticeAlreadyGiven,
+    #[msg("No termination notice has been given")]
+    NoNoticeGiven,
+    #[msg("Notice period has not passed")]
+    NoticePeriodNotOver,
+    #[msg("Token account does not belong to the expected party")]
+    InvalidTokenAccount,
+    #[msg("Too many co-tenants")]
+    TooManyTenants,
+    #[msg("Tenant shares must be positive and leave the primary tenant a share")]
+    InvalidTenantShare,
+    #[msg("Tenant is listed more than once")]
+    DuplicateTenant,
+    #[msg("Co-tenant accounts do not match the co-tenant list")]
+    CoTenantAccountMismatch,
+    #[msg("Guarantor cannot be a party to the lease")]
+    InvalidGuarantor,
+    #[msg("Signer is not a tenant on this lease")]
+    NotTenant,
+    #[msg("Signer is not the guarantor of this lease")]
+    NotGuarantor,
+    #[msg("Guarantee cap must be greater than 0")]
+    InvalidGuaranteeCap,
+    #[msg("Guarantee has already been accepted")]
+    GuaranteeAlreadyAccepted,
+    #[msg("Guarantor has not accepted a guarantee")]
+    NoGuarantee,
+    #[msg("Tenant has not defaulted")]
+    TenantNotDefaulted,
+    #[msg("Guarantee cap has been reached")]
+    GuaranteeExhausted,
+    #[msg("Token account or mint does not match the lease's payment mint")]
+    InvalidMint,
+    #[msg("Autopay allowance must be greater than 0")]
+    InvalidAllowance,
+    #[msg("Autopay is already enabled")]
+    AutopayAlreadyEnabled,
+    #[msg("Autopay is not enabled")]
+    AutopayNotEnabled,
+    #[msg("Instruction does not apply to this lease's payment mode")]
+    WrongPaymentMode,
+    #[msg("No streamed rent is available to withdraw")]
+    NothingStreamed,
+    #[msg("Settle the rent stream first")]
+    StreamNotSettled,
+    #[msg("Rent stream is not open")]
+    StreamNotOpen,
+    #[msg("Platform fee exceeds the maximum")]
+    FeeTooHigh,
+    #[msg("Token account is not owned by the platform treasury")]
+    InvalidTreasury,
+    #[msg("No deposit yield strategy is configured")]
+    YieldNotSupported,
+    #[msg("Deposit yield can only be offered with an approval, and at most 100% to the tenant")]
+    InvalidYieldShare,
+    #[msg("Tenant reputation is below the listing's minimum")]
+    ReputationTooLow,
+    #[msg("Application has not been approved by the landlord")]
+    ApplicationNotApproved,
+    #[msg("Application has already been reviewed")]
+    ApplicationNotPending,
+    #[msg("Application is still open")]
+    ApplicationStillOpen,
+    #[msg("Application has already been settled")]
+    ApplicationNotOpen,
+    #[msg("Application approval has expired")]
+    ApprovalExpired,
+    #[msg("Lease cannot start in the past")]
+    InvalidStartDate,
+    #[msg("Both parties' token accounts are required to pay the break fee")]
+    BreakFeeAccountsRequired,
+    #[msg("Landlord cannot lease their own property")]
+    SelfLease,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_700_000_000;
+    const WEEK: i64 = 604_800;
+
+    /// Four-week lease of 1_000 per week, due at the start of each week
+    fn lease() -> RentalAgreement {
+        let tenant = Pubkey::new_unique();
+        RentalAgreement {
+            property: Pubkey::new_unique(),
+            landlord: Pubkey::new_unique(),
+            tenant,
+            start_date: START,
+            end_date: START + 4 * WEEK,
+            rent_amount: 1_000,
+            deposit_amount: 2_000,
+            escrow: Pubkey::new_unique(),
+            payment_count: 0,
+            is_active: true,
+            last_payment_date: 0,
+            last_payment_amount: 0,
+            total_paid: 0,
+            payment_status: PaymentStatus::Current,
+            bump: 255,
+            late_fee_policy: LateFeePolicy {
+                grace_period: 86_400,
+                flat_fee: 50,
+                fee_bps: 500,
+                default_after: 7 * 86_400,
+            },
+            accrued_late_fee: 0,
+            billing_anchor: START,
+            billing_period: BillingPeriod::Weekly,
+            max_prepaid_periods: 1,
+            periods_paid: 0,
+            next_due_date: START,
+            pending_renewal: None,
+            scheduled_rent: None,
+            termination_terms: TerminationTerms::default(),
+            termination_notice: None,
+            tenant_shares: vec![TenantShare {
+                tenant,
+                share_bps: 10_000,
+            }],
+            guarantor: None,
+            period_collected: 0,
+            guarantee: None,
+            payment_mint: Pubkey::new_unique(),
+            autopay: None,
+            payment_mode: PaymentMode::Periodic,
+            stream_accrued: 0,
+            stream_checkpoint: START,
+            stream_open: false,
+        }
+    }
+
+    #[test]
+    fn co_tenant_payments_close_the_period_once_rent_is_covered() {
+        let mut agreement = lease();
+        let key = Pubkey::new_unique();
+        let co_tenant = Pubkey::new_unique();
+        let tenant = agreement.tenant;
+
+        assert_eq!(record_rent_payment(&mut agreement, key, co_tenant, 300, START), None);
+        assert_eq!(agreement.outstanding_rent(), 700);
+        assert_eq!(agreement.periods_paid, 0);
+
+        assert_eq!(record_rent_payment(&mut agreement, key, tenant, 700, START), Some(true));
+        assert_eq!(agreement.periods_paid, 1);
+        assert_eq!(agreement.period_collected, 0);
+        assert_eq!(agreement.next_due_date, START + WEEK);
+        assert_eq!(agreement.outstanding_rent(), 1_000);
+    }
+
+    #[test]
+    fn late_period_is_owed_with_its_fee_and_keeps_the_schedule() {
+        let mut agreement = lease();
+        let key = Pubkey::new_unique();
+        let tenant = agreement.tenant;
+
+        let now = START + 2 * 86_400;
+        agreement.assess_lateness(now);
+        assert_eq!(agreement.outstanding_rent(), 1_100);
+
+        assert_eq!(record_rent_payment(&mut agreement, key, tenant, 1_000, now), None);
+        assert_eq!(record_rent_payment(&mut agreement, key, tenant, 100, now), Some(false));
+        assert!(agreement.payment_status == PaymentStatus::Current);
+        assert_eq!(agreement.accrued_late_fee, 0);
+        assert_eq!(agreement.last_payment_amount, 1_100);
+
+        // Paying late does not move the next due date
+        assert_eq!(agreement.next_due_date, START + WEEK);
+    }
+
+    #[test]
+    fn streamed_rent_accrues_per_second_until_the_end_date() {
+        let mut agreement = lease();
+        agreement.payment_mode = PaymentMode::Streaming;
+
+        assert_eq!(agreement.streamed_rent(START - 60), 0);
+        assert_eq!(agreement.streamed_rent(START + WEEK / 2), 500);
+        assert_eq!(agreement.streamed_rent(START + 4 * WEEK), 4_000);
+        assert_eq!(agreement.streamed_rent(START + 10 * WEEK), 4_000);
+    }
+
+    #[test]
+    fn streamed_rent_continues_from_the_checkpoint() {
+        let mut agreement = lease();
+        agreement.payment_mode = PaymentMode::Streaming;
+        agreement.stream_accrued = 1_234;
+        agreement.stream_checkpoint = START + 2 * WEEK;
+
+        assert_eq!(agreement.streamed_rent(START + 3 * WEEK), 2_234);
+    }
+
+    fn renewal(rent_amount: u64) -> RenewalTerms {
+        RenewalTerms {
+            rent_amount,
+            lease_duration: 4 * WEEK,
+        }
+    }
+
+    #[test]
+    fn rent_decrease_leaves_a_part_paid_period_at_the_old_rent() {
+        let mut agreement = lease();
+        let key = Pubkey::new_unique();
+        let co_tenant = Pubkey::new_unique();
+
+        assert_eq!(record_rent_payment(&mut agreement, key, co_tenant, 600, START), None);
+        agreement.renew(renewal(500), START + 60);
+
+        assert_eq!(agreement.rent_amount, 1_000);
+        assert_eq!(agreement.outstanding_rent(), 400);
+        assert_eq!(agreement.end_date, START + 8 * WEEK);
+
+        let tenant = agreement.tenant;
+        assert_eq!(
+            record_rent_payment(&mut agreement, key, tenant, 400, START + 120),
+            Some(true)
+        );
+        assert_eq!(agreement.periods_paid, 1);
+        assert_eq!(agreement.total_paid, 1_000);
+    }
+
+    #[test]
+    fn renewed_rent_starts_with_the_first_period_of_the_new_term() {
+        let mut agreement = lease();
+        let key = Pubkey::new_unique();
+        let tenant = agreement.tenant;
+        agreement.renew(renewal(1_100), START);
+
+        for period in 0..4 {
+            assert_eq!(agreement.outstanding_rent(), 1_000);
+            let now = agreement.due_date_of(period);
+            record_rent_payment(&mut agreement, key, tenant, 1_000, now);
+        }
+
+        assert_eq!(agreement.rent_amount, 1_100);
+        assert!(agreement.scheduled_rent.is_none());
+        assert_eq!(agreement.outstanding_rent(), 1_100);
+    }
+
+    #[test]
+    fn outstanding_rent_saturates_when_rent_drops_below_collected() {
+        let mut agreement = lease();
+        agreement.period_collected = 600;
+        agreement.rent_amount = 500;
+
+        assert_eq!(agreement.outstanding_rent(), 0);
+    }
+
+    #[test]
+    fn streamed_rent_switches_rate_at_the_previous_end_date() {
+        let mut agreement = lease();
+        agreement.payment_mode = PaymentMode::Streaming;
+        agreement.renew(renewal(2_000), START + WEEK);
+
+        assert_eq!(agreement.streamed_rent(START + 4 * WEEK), 4_000);
+        assert_eq!(agreement.streamed_rent(START + 5 * WEEK), 6_000);
+
+        // Applying the new rate keeps what has already streamed
+        agreement.apply_scheduled_rent(START + 5 * WEEK);
+        assert_eq!(agreement.rent_amount, 2_000);
+        assert_eq!(agreement.streamed_rent(START + 6 * WEEK), 8_000);
+    }
+
+    #[test]
+    fn due_dates_step_from_the_billing_anchor() {
+        let mut agreement = lease();
+
+        assert_eq!(agreement.due_date_of(0), START);
+        assert_eq!(agreement.due_date_of(3), START + 3 * WEEK);
+
+        agreement.billing_period = BillingPeriod::Custom { seconds: 3_600 };
+        assert_eq!(agreement.due_date_of(2), START + 7_200);
+    }
+
+    #[test]
+    fn payment_count_keeps_counting_past_255_periods() {
+        let mut agreement = lease();
+        let key = Pubkey::new_unique();
+        let tenant = agreement.tenant;
+        agreement.billing_period = BillingPeriod::Custom { seconds: 60 };
+        agreement.end_date = START + 300 * 60;
+
+        for period in 0..300 {
+            let now = agreement.due_date_of(period);
+            assert_eq!(record_rent_payment(&mut agreement, key, tenant, 1_000, now), Some(true));
+        }
+
+        assert_eq!(agreement.payment_count, 300);
+        assert_eq!(agreement.periods_paid, 300);
+    }
+
+    #[test]
+    fn guarantor_draw_records_a_late_period_and_the_new_default() {
+        let mut agreement = lease();
+        let key = Pubkey::new_unique();
+        let guarantor = Pubkey::new_unique();
+
+        let now = START + 7 * 86_400 + 1;
+        agreement.assess_lateness(now);
+        assert!(agreement.payment_status == PaymentStatus::Defaulted);
+
+        let closed_on_time = record_rent_payment(&mut agreement, key, guarantor, 1_100, now);
+        assert_eq!(closed_on_time, Some(false));
+        assert!(
+            payment_reputation_events(closed_on_time, true, agreement.payment_count)
+                == vec![ReputationEvent::LatePayment, ReputationEvent::Default]
+        );
+    }
+
+    #[test]
+    fn partial_payments_record_no_reputation() {
+        assert!(payment_reputation_events(None, false, 0).is_empty());
+        assert!(payment_reputation_events(Some(true), false, 1) == vec![ReputationEvent::OnTimePayment]);
+    }
+
+    #[test]
+    fn on_time_credit_is_capped_per_lease() {
+        assert!(
+            payment_reputation_events(Some(true), false, MAX_CREDITED_PAYMENTS)
+                == vec![ReputationEvent::OnTimePayment]
+        );
+        assert!(payment_reputation_events(Some(true), false, MAX_CREDITED_PAYMENTS + 1).is_empty());
+
+        // Late payments always count
+        assert!(
+            payment_reputation_events(Some(false), false, MAX_CREDITED_PAYMENTS + 1)
+                == vec![ReputationEvent::LatePayment]
+        );
+    }
+
+    fn listing() -> Property {
+        Property {
+            landlord: Pubkey::new_unique(),
+            property_id: 1,
+            rent_amount: 1_000,
+            deposit_amount: 2_000,
+            lease_duration: 4 * WEEK,
+            status: PropertyStatus::Available,
+            is_available: true,
+            is_verified: false,
+            verification_level: 0,
+            metadata_uri: String::new(),
+            document_hash: String::new(),
+            total_rentals: 0,
+            successful_rentals: 0,
+            bump: 255,
+            created_at: 0,
+            late_fee_policy: LateFeePolicy::default(),
+            billing_period: BillingPeriod::Weekly,
+            max_prepaid_periods: 1,
+            termination_terms: TerminationTerms::default(),
+            payment_mint: Pubkey::new_unique(),
+            payment_mode: PaymentMode::Periodic,
+            verified_by: Pubkey::default(),
+            verification_requested_at: 0,
+            min_tenant_score: 0,
+            application_deposit: 10,
+            open_windows: Vec::new(),
+            blocked_ranges: Vec::new(),
+            bookings: Vec::new(),
+            last_booked_at: 0,
+        }
+    }
+
+    /// Application submitted a day before START, approved until a week after
+    fn application(status: ApplicationStatus) -> Application {
+        Application {
+            property: Pubkey::new_unique(),
+            landlord: Pubkey::new_unique(),
+            tenant: Pubkey::new_unique(),
+            profile_hash: [0; 32],
+            mint: Pubkey::new_unique(),
+            deposit_amount: 10,
+            status,
+            created_at: START - 86_400,
+            bump: 255,
+            tenant_yield_share_bps: None,
+            approval_expires_at: START + WEEK,
+        }
+    }
+
+    #[test]
+    fn approval_survives_a_booking_for_other_dates() {
+        let mut property = listing();
+        let approved = application(ApplicationStatus::Approved);
+        let pending = application(ApplicationStatus::Pending);
+
+        let booked = DateRange {
+            start: START,
+            end: START + 4 * WEEK,
+        };
+        property.book(Pubkey::new_unique(), booked, START).unwrap();
+
+        // The approved tenant can still lease the weeks after the booking
+        let later = DateRange {
+            start: START + 4 * WEEK,
+            end: START + 8 * WEEK,
+        };
+        assert!(property.is_bookable(&later, None));
+        assert!(!approved.is_refundable(&property, START));
+        assert!(pending.is_refundable(&property, START));
+
+        // Until the approval expires
+        assert!(approved.is_refundable(&property, START + WEEK));
+    }
+
+    #[test]
+    fn open_applications_lapse_when_the_property_is_deactivated() {
+        let mut property = listing();
+        property.status = PropertyStatus::Deactivated;
+
+        assert!(application(ApplicationStatus::Approved).is_refundable(&property, START));
+        assert!(application(ApplicationStatus::Pending).is_refundable(&property, START));
+        assert!(application(ApplicationStatus::Rejected).is_refundable(&listing(), START));
+        assert!(!application(ApplicationStatus::Pending).is_refundable(&listing(), START));
+    }
+
+    #[test]
+    fn counterparty_is_the_other_side_of_the_lease() {
+        let agreement = lease();
+
+        assert_eq!(agreement.counterparty(&agreement.tenant), Some(agreement.landlord));
+        assert_eq!(agreement.counterparty(&agreement.landlord), Some(agreement.tenant));
+        assert_eq!(agreement.counterparty(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn assess_lateness_waits_out_the_grace_period() {
+        let mut agreement = lease();
+
+        assert_eq!(agreement.assess_lateness(START + 86_400), 0);
+        assert!(agreement.payment_status == PaymentStatus::Current);
+        assert_eq!(agreement.accrued_late_fee, 0);
+    }
+
+    #[test]
+    fn assess_lateness_charges_the_fee_once_per_missed_payment() {
+        let mut agreement = lease();
+
+        // 50 flat plus 5% of 1_000
+        assert_eq!(agreement.assess_lateness(START + 86_401), 100);
+        assert!(agreement.payment_status == PaymentStatus::Late);
+
+        assert_eq!(agreement.assess_lateness(START + 2 * 86_400), 0);
+        assert_eq!(agreement.accrued_late_fee, 100);
+    }
+
+    #[test]
+    fn assess_lateness_defaults_after_the_threshold() {
+        let mut agreement = lease();
+
+        assert_eq!(agreement.assess_lateness(START + 7 * 86_400 + 1), 100);
+        assert!(agreement.payment_status == PaymentStatus::Defaulted);
+        assert_eq!(agreement.accrued_late_fee, 100);
+    }
+
+    #[test]
+    fn assess_lateness_never_defaults_without_a_threshold() {
+        let mut agreement = lease();
+        agreement.late_fee_policy.default_after = 0;
+
+        agreement.assess_lateness(START + 3 * WEEK);
+        assert!(agreement.payment_status == PaymentStatus::Late);
+    }
+
+    #[test]
+    fn assess_lateness_ignores_streaming_leases() {
+        let mut agreement = lease();
+        agreement.payment_mode = PaymentMode::Streaming;
+
+        assert_eq!(agreement.assess_lateness(START + 3 * WEEK), 0);
+        assert!(agreement.payment_status == PaymentStatus::Current);
+    }
 }
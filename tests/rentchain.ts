@@ -1,15 +1,71 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { assert } from "chai";
+import { PropertyRegistry } from "../target/types/property_registry";
+import { RentalAgreement } from "../target/types/rental_agreement";
+import { Escrow } from "../target/types/escrow";
+
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
+const RENT = new anchor.BN(1_000_000_000); // 1,000 tokens with 6 decimals
+const DEPOSIT = new anchor.BN(2_000_000_000);
+const APPLICATION_DEPOSIT = new anchor.BN(10_000_000);
+const FEE_BPS = 100;
+const DAY = 86_400;
 
 describe("RentChain Tests", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
 
+  const registry = anchor.workspace.PropertyRegistry as Program<PropertyRegistry>;
+  const rental = anchor.workspace.RentalAgreement as Program<RentalAgreement>;
+  const escrow = anchor.workspace.Escrow as Program<Escrow>;
+  const admin = (provider.wallet as anchor.Wallet).payer;
+
   let landlord: Keypair;
   let tenant: Keypair;
   let propertyId: anchor.BN;
+  let mint: PublicKey;
+  let landlordTokenAccount: PublicKey;
+  let tenantTokenAccount: PublicKey;
+  let treasuryTokenAccount: PublicKey;
+
+  let property: PublicKey;
+  let application: PublicKey;
+  let applicationVault: PublicKey;
+  let rentalAgreement: PublicKey;
+  let escrowAccount: PublicKey;
+  let escrowTokenAccount: PublicKey;
+
+  const rentalConfig = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    rental.programId
+  )[0];
+  const rentalAuthority = PublicKey.findProgramAddressSync(
+    [Buffer.from("rental_authority")],
+    rental.programId
+  )[0];
+
+  const reputationOf = (role: "tenant" | "landlord", wallet: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation"), Buffer.from(role), wallet.toBuffer()],
+      registry.programId
+    )[0];
+
+  const balanceOf = async (tokenAccount: PublicKey) =>
+    Number((await getAccount(provider.connection, tokenAccount)).amount);
 
   before(async () => {
     landlord = Keypair.generate();
@@ -30,26 +86,261 @@ describe("RentChain Tests", () => {
         2 * anchor.web3.LAMPORTS_PER_SOL
       )
     );
+
+    // Stand-in for USDC, with the provider wallet as platform treasury
+    mint = await createMint(provider.connection, admin, admin.publicKey, null, 6);
+    landlordTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, admin, mint, landlord.publicKey)
+    ).address;
+    tenantTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, admin, mint, tenant.publicKey)
+    ).address;
+    treasuryTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, admin, mint, admin.publicKey)
+    ).address;
+    await mintTo(provider.connection, admin, mint, tenantTokenAccount, admin, 10_000_000_000);
+
+    // The rental config is claimed once per cluster by the upgrade authority
+    if ((await provider.connection.getAccountInfo(rentalConfig)) === null) {
+      const programData = PublicKey.findProgramAddressSync(
+        [rental.programId.toBuffer()],
+        BPF_LOADER_UPGRADEABLE_ID
+      )[0];
+
+      await rental.methods
+        .initializeConfig(
+          admin.publicKey,
+          new anchor.BN(3 * DAY),
+          new anchor.BN(14 * DAY),
+          500,
+          FEE_BPS,
+          admin.publicKey,
+          PublicKey.default,
+          new anchor.BN(7 * DAY)
+        )
+        .accounts({
+          config: rentalConfig,
+          admin: admin.publicKey,
+          program: rental.programId,
+          programData,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    property = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("property"),
+        landlord.publicKey.toBuffer(),
+        propertyId.toArrayLike(Buffer, "le", 8),
+      ],
+      registry.programId
+    )[0];
+    application = PublicKey.findProgramAddressSync(
+      [Buffer.from("application"), property.toBuffer(), tenant.publicKey.toBuffer()],
+      rental.programId
+    )[0];
+    applicationVault = getAssociatedTokenAddressSync(mint, application, true);
+    rentalAgreement = PublicKey.findProgramAddressSync(
+      [Buffer.from("rental"), property.toBuffer(), tenant.publicKey.toBuffer()],
+      rental.programId
+    )[0];
+    escrowAccount = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), rentalAgreement.toBuffer()],
+      escrow.programId
+    )[0];
+    escrowTokenAccount = getAssociatedTokenAddressSync(mint, escrowAccount, true);
   });
 
   describe("Property Registry", () => {
     it("Creates a property listing", async () => {
-      // Test will be implemented after deployment
-      console.log("Property Registry test placeholder");
+      await registry.methods
+        .createListing(
+          propertyId,
+          RENT,
+          DEPOSIT,
+          new anchor.BN(365 * DAY),
+          "ipfs://rentchain-test-listing",
+          {
+            gracePeriod: new anchor.BN(3 * DAY),
+            flatFee: new anchor.BN(0),
+            feeBps: 500,
+            defaultAfter: new anchor.BN(30 * DAY),
+          },
+          { monthly: {} },
+          1,
+          { noticePeriod: new anchor.BN(30 * DAY), breakFee: new anchor.BN(0) },
+          { periodic: {} },
+          0,
+          APPLICATION_DEPOSIT
+        )
+        .accounts({
+          property,
+          landlord: landlord.publicKey,
+          paymentMint: mint,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlord])
+        .rpc();
+
+      const listing = await registry.account.property.fetch(property);
+      assert.ok(listing.landlord.equals(landlord.publicKey));
+      assert.ok(listing.rentAmount.eq(RENT));
+      assert.ok(listing.paymentMint.equals(mint));
+      assert.deepEqual(listing.status, { available: {} });
     });
   });
 
   describe("Rental Agreement", () => {
-    it("Creates a lease agreement", async () => {
-      // Test will be implemented after deployment
-      console.log("Rental Agreement test placeholder");
+    it("Submits an application with the listing's deposit", async () => {
+      await rental.methods
+        .submitApplication(Array(32).fill(7))
+        .accounts({
+          application,
+          property,
+          tenant: tenant.publicKey,
+          mint,
+          tenantTokenAccount,
+          applicationVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([tenant])
+        .rpc();
+
+      const submitted = await rental.account.application.fetch(application);
+      assert.deepEqual(submitted.status, { pending: {} });
+      assert.equal(await balanceOf(applicationVault), APPLICATION_DEPOSIT.toNumber());
+    });
+
+    it("Approves the application and opens the landlord's reputation", async () => {
+      await rental.methods
+        .reviewApplication(true, null)
+        .accounts({
+          application,
+          config: rentalConfig,
+          landlord: landlord.publicKey,
+          landlordReputation: reputationOf("landlord", landlord.publicKey),
+          propertyProgram: registry.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([landlord])
+        .rpc();
+
+      const approved = await rental.account.application.fetch(application);
+      assert.deepEqual(approved.status, { approved: {} });
+
+      const reputation = await registry.account.reputation.fetch(
+        reputationOf("landlord", landlord.publicKey)
+      );
+      assert.equal(reputation.score, 500);
+    });
+
+    it("Creates a lease agreement and funds the escrow", async () => {
+      await rental.methods
+        .createLease([], null, null)
+        .accounts({
+          rentalAgreement,
+          config: rentalConfig,
+          property,
+          landlord: landlord.publicKey,
+          tenant: tenant.publicKey,
+          tenantReputation: reputationOf("tenant", tenant.publicKey),
+          application,
+          rentalAuthority,
+          propertyProgram: registry.programId,
+          escrow: escrowAccount,
+          mint,
+          tenantTokenAccount,
+          escrowTokenAccount,
+          escrowProgram: escrow.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([tenant])
+        .rpc();
+
+      const agreement = await rental.account.rentalAgreement.fetch(rentalAgreement);
+      assert.ok(agreement.isActive);
+      assert.ok(agreement.rentAmount.eq(RENT));
+      assert.ok(agreement.escrow.equals(escrowAccount));
+
+      const listing = await registry.account.property.fetch(property);
+      assert.deepEqual(listing.status, { rented: {} });
+      assert.equal(listing.bookings.length, 1);
+
+      const leased = await rental.account.application.fetch(application);
+      assert.deepEqual(leased.status, { leased: {} });
+    });
+
+    it("Pays the first period's rent less the platform fee", async () => {
+      await rental.methods
+        .payRent(RENT)
+        .accounts({
+          rentalAgreement,
+          landlord: landlord.publicKey,
+          payer: tenant.publicKey,
+          mint,
+          payerTokenAccount: tenantTokenAccount,
+          landlordTokenAccount,
+          config: rentalConfig,
+          treasuryTokenAccount,
+          tenantReputation: reputationOf("tenant", tenant.publicKey),
+          rentalAuthority,
+          propertyProgram: registry.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([tenant])
+        .rpc();
+
+      const agreement = await rental.account.rentalAgreement.fetch(rentalAgreement);
+      assert.equal(agreement.periodsPaid, 1);
+      assert.ok(agreement.totalPaid.eq(RENT));
+
+      const fee = (RENT.toNumber() * FEE_BPS) / 10_000;
+      assert.equal(await balanceOf(landlordTokenAccount), RENT.toNumber() - fee);
+
+      const reputation = await registry.account.reputation.fetch(
+        reputationOf("tenant", tenant.publicKey)
+      );
+      assert.equal(reputation.onTimePayments, 1);
+    });
+
+    it("Refunds the application deposit once leased", async () => {
+      const before = await balanceOf(tenantTokenAccount);
+
+      await rental.methods
+        .refundApplication()
+        .accounts({
+          application,
+          property,
+          tenant: tenant.publicKey,
+          mint,
+          applicationVault,
+          tenantTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      assert.equal(
+        await balanceOf(tenantTokenAccount),
+        before + APPLICATION_DEPOSIT.toNumber()
+      );
+      assert.isNull(await provider.connection.getAccountInfo(application));
     });
   });
 
   describe("Escrow", () => {
-    it("Deposits to escrow", async () => {
-      // Test will be implemented after deployment
-      console.log("Escrow test placeholder");
+    it("Holds the security deposit in the escrow vault", async () => {
+      const held = await escrow.account.escrow.fetch(escrowAccount);
+      assert.ok(held.tenant.equals(tenant.publicKey));
+      assert.ok(held.landlord.equals(landlord.publicKey));
+      assert.ok(held.amount.eq(DEPOSIT));
+      assert.isFalse(held.isReleased);
+      assert.equal(await balanceOf(escrowTokenAccount), DEPOSIT.toNumber());
     });
   });
 });